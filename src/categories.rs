@@ -1,6 +1,5 @@
 use crate::categories::classifiers::classify_url;
 use crate::categories::enums::Categories;
-use crate::logger::tgnews_debug;
 use crate::news::{is_news, is_news_ru};
 use crate::utils::{clean, split_files_for_threads};
 use fasttext::FastText;
@@ -234,11 +233,7 @@ pub fn classifier_entry(path: &str, threads: usize) {
     let entertainment = Arc::new(Mutex::new(Vec::with_capacity(1000)));
     let science = Arc::new(Mutex::new(Vec::with_capacity(1000)));
     let other = Arc::new(Mutex::new(Vec::with_capacity(1000)));
-    tgnews_debug(format!(
-        "Files split into  {} for {} worker threads",
-        small_paths[0].len(),
-        threads
-    ));
+    debug!(files = small_paths[0].len(), threads = threads; "Files split for worker threads");
     let time_now = Instant::now();
     crossbeam_utils::thread::scope(|s| {
         for range in small_paths {
@@ -264,10 +259,7 @@ pub fn classifier_entry(path: &str, threads: usize) {
         }
     })
     .expect("Could not spawn threads");
-    tgnews_debug(format!(
-        "Spent {} seconds categorizing files",
-        time_now.elapsed().as_secs()
-    ));
+    debug!(duration = time_now.elapsed().as_secs_f64(); "Finished categorizing files");
     format_output(
         &*society.lock().unwrap(),
         &*economy.lock().unwrap(),
@@ -288,8 +280,16 @@ fn format_output(
     science: &[String],
     other: &[String],
 ) {
-    tgnews_debug(format!("Society:\t{}\n\tEconomy:\t{}\n\tTechnology:\t{}\n\tSports:\t\t{}\n\tEntertainment:\t{}\n\tScience:\t{}\n\tOther:\t\t{}\n",
-                         society.len(),economy.len(),technology.len(),sports.len(),entertainment.len(),science.len(),other.len()));
+    debug!(
+        society = society.len(),
+        economy = economy.len(),
+        technology = technology.len(),
+        sports = sports.len(),
+        entertainment = entertainment.len(),
+        science = science.len(),
+        other = other.len();
+        "Articles per category"
+    );
     let vec_holder = vec![
         json!({"category":"society","articles":society}),
         json!({"category":"economy","articles":economy}),
@@ -40,6 +40,19 @@ impl Default for Categories {
     }
 }
 impl Categories {
+    /// The category with this name, as written by `Display`, `None` for unknown names
+    pub fn from_name(name: &str) -> Option<Categories> {
+        match name {
+            "economy" => Some(Self::Economy),
+            "society" => Some(Self::Society),
+            "technology" => Some(Self::Technology),
+            "sports" => Some(Self::Sports),
+            "entertainment" => Some(Self::Entertainment),
+            "science" => Some(Self::Science),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
     /// Convert a category to a i32
     /// to be used in protobuf
     pub fn to_i32(self) -> i32 {
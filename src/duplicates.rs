@@ -0,0 +1,235 @@
+//! Finding copies of the same story
+//!
+//! Wire stories get republished with small edits, the title may change but the body barely does.
+//! Each article gets a 64 bit SimHash of its cleaned body: every 3 word shingle is hashed and each hash
+//! votes on the bits, so similar bodies end up with hashes differing in a few bits. Articles whose hashes
+//! are at most `MAX_DISTANCE` bits apart are near duplicates.
+//!
+//! To avoid comparing every pair, hashes are split into four 16 bit bands, two hashes within
+//! `MAX_DISTANCE` bits share at least one band exactly, so only articles sharing a band are compared.
+//!
+//! Within a group the oldest article is the original, the others are its duplicates.
+//! `tgnews duplicates <dir>` prints the groups in a directory, the server marks each upload with the
+//! article it duplicates(see `cluster::cluster`) and `GET /threads` can leave duplicates out
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use select::document::Document;
+use select::predicate::{Attr, Name};
+use serde::Serialize;
+
+use crate::utils::{clean, fnv1a, split_files_for_threads};
+
+/// Most bits two hashes may differ in for their articles to be near duplicates
+pub const MAX_DISTANCE: u32 = 3;
+const SHINGLE: usize = 3;
+const BANDS: usize = 4;
+
+/// SimHash of a cleaned body, 0 if it's too short to tell anything
+pub fn simhash(body: &str) -> u64 {
+    let words: Vec<&str> = body.split_whitespace().collect();
+    if words.len() < SHINGLE {
+        return 0;
+    }
+    let mut votes = [0_i32; 64];
+    for shingle in words.windows(SHINGLE) {
+        let hash = fnv1a(shingle.join(" ").as_bytes());
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            if hash & (1 << bit) == 0 {
+                *vote -= 1;
+            } else {
+                *vote += 1;
+            }
+        }
+    }
+    votes
+        .iter()
+        .enumerate()
+        .filter(|(_, &vote)| vote > 0)
+        .fold(0, |hash, (bit, _)| hash | 1 << bit)
+}
+
+/// Whether two hashes are near duplicates
+pub fn is_near(a: u64, b: u64) -> bool {
+    (a ^ b).count_ones() <= MAX_DISTANCE
+}
+
+fn band(hash: u64, band: usize) -> u16 {
+    (hash >> (band * 16)) as u16
+}
+
+/// Articles by SimHash
+#[derive(Default)]
+pub struct DuplicateIndex {
+    /// name to (hash, publish time)
+    hashes: HashMap<String, (u64, u64)>,
+    bands: [HashMap<u16, Vec<String>>; BANDS],
+}
+
+impl DuplicateIndex {
+    pub fn new() -> DuplicateIndex {
+        DuplicateIndex::default()
+    }
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+    /// Add an article, articles with a zero hash are left out
+    pub fn insert(&mut self, name: &str, hash: u64, published: u64) {
+        if hash == 0 {
+            return;
+        }
+        self.remove(name);
+        self.hashes.insert(name.to_string(), (hash, published));
+        for (i, bands) in self.bands.iter_mut().enumerate() {
+            bands.entry(band(hash, i)).or_default().push(name.to_string());
+        }
+    }
+    pub fn remove(&mut self, name: &str) {
+        if let Some((hash, _)) = self.hashes.remove(name) {
+            for (i, bands) in self.bands.iter_mut().enumerate() {
+                if let Some(names) = bands.get_mut(&band(hash, i)) {
+                    names.retain(|n| n != name);
+                    if names.is_empty() {
+                        bands.remove(&band(hash, i));
+                    }
+                }
+            }
+        }
+    }
+    /// Articles whose hash is near `hash`
+    pub fn similar(&self, hash: u64) -> Vec<&str> {
+        if hash == 0 {
+            return vec![];
+        }
+        let mut found = HashSet::new();
+        for (i, bands) in self.bands.iter().enumerate() {
+            for name in bands.get(&band(hash, i)).into_iter().flatten() {
+                if is_near(hash, self.hashes[name].0) {
+                    found.insert(name.as_str());
+                }
+            }
+        }
+        found.into_iter().collect()
+    }
+    /// The original of an article with this hash, if we have one
+    pub fn original_of(&self, hash: u64) -> Option<String> {
+        self.similar(hash)
+            .into_iter()
+            .min_by_key(|name| (self.hashes[*name].1, *name))
+            .map(String::from)
+    }
+    /// Groups of near duplicates with more than one article, each with its original first
+    ///
+    /// Near is transitive here, `a` and `c` land in one group if both are near `b`
+    pub fn groups(&self) -> Vec<Vec<String>> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut groups: Vec<Vec<&str>> = vec![];
+        for name in self.hashes.keys() {
+            if !seen.insert(name.as_str()) {
+                continue;
+            }
+            // walk everything reachable from `name`
+            let mut group = vec![name.as_str()];
+            let mut next = 0;
+            while next < group.len() {
+                for similar in self.similar(self.hashes[group[next]].0) {
+                    if seen.insert(similar) {
+                        group.push(similar);
+                    }
+                }
+                next += 1;
+            }
+            groups.push(group);
+        }
+        let mut groups: Vec<Vec<String>> = groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|mut group| {
+                group.sort_by_key(|name| (self.hashes[*name].1, *name));
+                group.into_iter().map(String::from).collect()
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+    /// The original of every article that isn't the original of its group
+    pub fn originals(&self) -> HashMap<String, String> {
+        let mut originals = HashMap::new();
+        for mut group in self.groups() {
+            let original = group.remove(0);
+            for duplicate in group {
+                originals.insert(duplicate, original.clone());
+            }
+        }
+        originals
+    }
+}
+
+#[derive(Serialize)]
+struct DuplicateGroup {
+    original: String,
+    duplicates: Vec<String>,
+}
+
+/// Hash the body of every HTML file in `paths`
+fn hash_files(paths: &[String], index: Arc<Mutex<DuplicateIndex>>) {
+    for file in paths.iter().filter(|file| file.ends_with(".html")) {
+        let html = match read_to_string(file) {
+            Ok(html) => html,
+            Err(e) => {
+                warn!(file = file.as_str(); "Could not read file\n{}", e);
+                continue;
+            }
+        };
+        let doc = Document::from(html.as_str());
+        let mut body = String::with_capacity(1000);
+        doc.find(Name("p")).for_each(|p| body.push_str(&p.text()));
+        let published = doc
+            .find(Attr("property", "article:published_time"))
+            .next()
+            .and_then(|meta| meta.attr("content"))
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+            .map_or(0, |time| time.timestamp().max(0) as u64);
+        let name = file.split('/').last().unwrap();
+        index
+            .lock()
+            .unwrap()
+            .insert(name, simhash(clean(body, false).as_str()), published);
+    }
+}
+
+/// Entry point for `tgnews duplicates`, prints the groups of near duplicates in `dir`
+pub fn entry(dir: &str, threads: usize) {
+    assert!(Path::new(dir).exists(), "Path {:?} not found", dir);
+    let small_paths = split_files_for_threads(dir.to_string(), threads);
+    let index = Arc::new(Mutex::new(DuplicateIndex::new()));
+    let time = Instant::now();
+    crossbeam_utils::thread::scope(|s| {
+        for range in small_paths {
+            let index = index.clone();
+            s.spawn(move |_| hash_files(range.as_slice(), index));
+        }
+    })
+    .expect("Could not spawn threads");
+    let groups: Vec<DuplicateGroup> = index
+        .lock()
+        .unwrap()
+        .groups()
+        .into_iter()
+        .map(|mut group| DuplicateGroup {
+            original: group.remove(0),
+            duplicates: group,
+        })
+        .collect();
+    info!(groups = groups.len(), duration = time.elapsed().as_secs_f64(); "Found near duplicates");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({ "groups": groups })).unwrap()
+    );
+}
@@ -0,0 +1,192 @@
+//! How an article is turned into the vector it's clustered by
+//!
+//! * `title`, the sentence vector of the title(what we always did)
+//! * `title_lead`, the sentence vector of the title followed by the lead, the first `LEAD_WORDS` words of the body
+//! * `tfidf`, the mean of the title's sentence vector and the TF-IDF weighted mean of the body's word vectors
+//!
+//! Document frequencies for `tfidf` are counted as articles come in, so early articles are weighted against
+//! fewer documents than late ones. A body is only counted once, however often it's embedded, and counting
+//! stops after `MAX_DOCUMENTS` bodies so the counts don't grow without bound. The server keeps them next to
+//! the embeddings(see `snapshot::save_frequencies`), so after a restart new articles are weighted like the
+//! ones whose vectors it cached. The `threads` command starts from nothing every run. Articles without
+//! a body(the server doesn't keep bodies, see `snapshot::embedding`) fall back to their title, `embed` says
+//! which representation it used and `fallbacks` how often it fell back.
+//!
+//! The `threads` command takes `--representation`, the server `representation` in `Rocket.toml`, and both
+//! report the one used next to the threads
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use fasttext::FastText;
+use serde::{Deserialize, Serialize};
+
+/// Body words used by `title_lead`
+const LEAD_WORDS: usize = 40;
+/// Bodies counted into the document frequencies, later ones are weighted against them without being counted
+const MAX_DOCUMENTS: u64 = 100_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Representation {
+    Title,
+    TitleLead,
+    Tfidf,
+}
+
+impl Default for Representation {
+    fn default() -> Self {
+        Representation::Title
+    }
+}
+
+impl Representation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Representation::Title => "title",
+            Representation::TitleLead => "title_lead",
+            Representation::Tfidf => "tfidf",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Representation> {
+        match name {
+            "title" => Some(Representation::Title),
+            "title_lead" => Some(Representation::TitleLead),
+            "tfidf" => Some(Representation::Tfidf),
+            _ => None,
+        }
+    }
+}
+
+/// Documents seen and how many of them each word appeared in
+#[derive(Default, Serialize, Deserialize)]
+struct DocumentFrequencies {
+    documents: u64,
+    words: HashMap<String, u64>,
+    /// Hashes of the bodies counted, so embedding one again doesn't count it twice
+    counted: HashSet<u64>,
+    /// Counted since the last `take_frequencies`
+    #[serde(skip)]
+    changed: bool,
+}
+
+lazy_static! {
+    static ref REPRESENTATION: RwLock<Representation> = RwLock::new(Representation::default());
+    static ref FREQUENCIES: Mutex<DocumentFrequencies> = Mutex::new(DocumentFrequencies::default());
+    /// Articles embedded by their title because the representation had nothing else to go on
+    static ref FALLBACKS: AtomicU64 = AtomicU64::new(0);
+}
+
+/// Embed articles this way from now on
+pub fn configure(representation: Representation) {
+    *REPRESENTATION.write().unwrap() = representation;
+}
+
+/// The representation in use
+pub fn representation() -> Representation {
+    *REPRESENTATION.read().unwrap()
+}
+
+/// The document frequencies as JSON, `None` if they didn't change since the last call
+pub fn take_frequencies() -> Option<Vec<u8>> {
+    let mut frequencies = FREQUENCIES.lock().unwrap();
+    if !frequencies.changed {
+        return None;
+    }
+    frequencies.changed = false;
+    serde_json::to_vec(&*frequencies).ok()
+}
+
+/// Weigh against document frequencies from `take_frequencies` from now on
+pub fn restore_frequencies(value: &[u8]) -> Result<(), String> {
+    let restored: DocumentFrequencies = serde_json::from_slice(value).map_err(|e| e.to_string())?;
+    *FREQUENCIES.lock().unwrap() = restored;
+    Ok(())
+}
+
+/// How many articles fell back to their title so far
+pub fn fallbacks() -> u64 {
+    FALLBACKS.load(Ordering::Relaxed)
+}
+
+/// Count a body's words, unless it was counted before or we counted enough, and return their TF-IDF weights
+#[allow(clippy::cast_precision_loss)]
+fn tf_idf(words: &[&str]) -> HashMap<String, f32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for word in words {
+        *counts.entry((*word).to_string()).or_insert(0) += 1;
+    }
+    let mut hasher = DefaultHasher::new();
+    words.hash(&mut hasher);
+    let hash = hasher.finish();
+    let mut frequencies = FREQUENCIES.lock().unwrap();
+    if frequencies.documents < MAX_DOCUMENTS && frequencies.counted.insert(hash) {
+        frequencies.documents += 1;
+        frequencies.changed = true;
+        for word in counts.keys() {
+            *frequencies.words.entry(word.clone()).or_insert(0) += 1;
+        }
+    }
+    let documents = frequencies.documents as f32;
+    counts
+        .into_iter()
+        .map(|(word, count)| {
+            // words only in bodies we didn't count are as rare as can be
+            let seen_in = frequencies.words.get(&word).copied().unwrap_or(0) as f32;
+            // smoothed, so a word in every document still counts a little
+            let idf = ((documents + 1.0) / (seen_in + 1.0)).ln() + 1.0;
+            let tf = count as f32 / words.len() as f32;
+            (word, tf * idf)
+        })
+        .collect()
+}
+
+/// The title's vector, counted as a fallback
+fn fallback(title_vector: Vec<f32>) -> (Vec<f32>, Representation) {
+    FALLBACKS.fetch_add(1, Ordering::Relaxed);
+    (title_vector, Representation::Title)
+}
+
+/// The vector of an article and the representation it's in, `Title` if it fell back to its title
+///
+/// `title` and `body` should already be cleaned the way the model expects
+pub fn embed(model: &FastText, title: &str, body: &str) -> (Vec<f32>, Representation) {
+    let title_vector = model.get_sentence_vector(title);
+    let words: Vec<&str> = body.split_whitespace().collect();
+    let representation = representation();
+    match representation {
+        Representation::Title => (title_vector, representation),
+        _ if words.is_empty() => fallback(title_vector),
+        Representation::TitleLead => {
+            let lead = words[..words.len().min(LEAD_WORDS)].join(" ");
+            let vector = model.get_sentence_vector(format!("{} {}", title, lead).as_str());
+            (vector, representation)
+        }
+        Representation::Tfidf => {
+            let mut body_vector = vec![0.0; title_vector.len()];
+            let mut total = 0.0;
+            for (word, weight) in tf_idf(&words) {
+                let vector = model.get_word_vector(word.as_str());
+                if vector.len() != body_vector.len() {
+                    continue;
+                }
+                body_vector
+                    .iter_mut()
+                    .zip(vector)
+                    .for_each(|(b, v)| *b += weight * v);
+                total += weight;
+            }
+            if total <= 0.0 {
+                return fallback(title_vector);
+            }
+            let vector = title_vector
+                .iter()
+                .zip(body_vector)
+                .map(|(t, b)| (t + b / total) / 2.0)
+                .collect();
+            (vector, representation)
+        }
+    }
+}
@@ -11,7 +11,6 @@ extern crate crossbeam_utils;
 // Pretty print json
 extern crate serde_json;
 
-use crate::logger;
 use select::document::Document;
 use select::predicate::Name;
 /// Language module
@@ -23,7 +22,6 @@ use std::sync::{Arc, Mutex};
 
 use serde_json::json;
 
-use crate::logger::tgnews_debug;
 use crate::utils::split_files_for_threads;
 use std::time::Instant;
 use whatlang::{Detector, Lang};
@@ -92,18 +90,14 @@ fn parse_files(
 /// Takes a `path` which is a directory containing html files.
 /// And a `thread` which specifies how many threads to spawn
 pub fn entry(path: &str, thread: usize) {
-    logger::tgnews_debug("Mode: \tLanguages");
+    debug!(mode = "languages"; "Starting");
     // Get filenames in the directory
     let small_paths = split_files_for_threads(path.to_string(), thread);
     // Declare mutable variables
     let en = Arc::new(Mutex::new(Vec::with_capacity(10000)));
     let ru = Arc::new(Mutex::new(Vec::with_capacity(10000)));
     //Spawn threads
-    tgnews_debug(format!(
-        "Files split into {} for {} worker threads",
-        small_paths[0].len(),
-        thread
-    ));
+    debug!(files = small_paths[0].len(), threads = thread; "Files split for worker threads");
     let time_now = Instant::now();
     crossbeam_utils::thread::scope(|scope| {
         for range in small_paths {
@@ -113,17 +107,10 @@ pub fn entry(path: &str, thread: usize) {
         }
     })
     .expect("Could not spawn threads");
-    tgnews_debug(format!(
-        "Finished classifying languages in {} seconds",
-        time_now.elapsed().as_secs()
-    ));
+    debug!(duration = time_now.elapsed().as_secs_f64(); "Finished classifying languages");
     let en_v = &*en.lock().unwrap();
     let ru_v = &*ru.lock().unwrap();
-    tgnews_debug(format!(
-        "English articles:\t{}\n\tRussian articles:\t{}\n",
-        en_v.len(),
-        ru_v.len()
-    ));
+    debug!(en = en_v.len(), ru = ru_v.len(); "Articles per language");
     // Call formatter
     format_for_output(en_v, ru_v);
 }
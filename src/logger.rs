@@ -1,26 +1,172 @@
+//! The logging backend
+//!
+//! Everything logs through the `log` macros, this is the logger behind them.
+//!
+//! It's configured from the environment
+//! * `TGNEWS_LOG`: levels, a default level followed by per module ones, e.g
+//! `warn,tgnews::server=info,tgnews::server::cluster=debug`. The longest matching module wins
+//! * `TGNEWS_LOG_FORMAT`: `json` for one JSON object per line, anything else for human friendly text
+//!
+//! Key-values passed to the macros(`info!(article = name, lang = "en"; "Stored article")`)
+//! become fields, that's how we attach the article id, language, category, durations and request ids
+//!
+//! Logs go to stderr since the cli prints its results to stdout
+use std::io::Write;
+
+use chrono::Local;
 use colored::*;
-use log::Level;
-
-pub fn log_msg<T>(level: log::Level, message: T)
-where
-    T: AsRef<str>,
-{
-    match level {
-        Level::Error => eprintln!("[{}]\t{}", level.to_string().red(), message.as_ref()),
-        Level::Warn => eprintln!("[{}]\t{}", level.to_string().red(), message.as_ref()),
-        Level::Info => {}
-        Level::Debug => eprintln!("[{}]\t{}", level.to_string().blue(), message.as_ref()),
-        Level::Trace => {}
+use log::kv::{Error, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value as Json};
+
+/// Level used when `TGNEWS_LOG` isn't set
+const DEFAULT_LEVEL: &str = "warn";
+
+pub struct TgnewsLogger {
+    default: LevelFilter,
+    /// `(module, level)` sorted with the longest module first
+    modules: Vec<(String, LevelFilter)>,
+    json: bool,
+}
+
+impl TgnewsLogger {
+    /// Parse a level spec, e.g `warn,tgnews::server=debug`
+    ///
+    /// Parts we can't understand are ignored with a warning
+    pub fn from_spec(spec: &str, json: bool) -> TgnewsLogger {
+        let mut default = LevelFilter::Warn;
+        let mut modules = vec![];
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (module, level) = match part.find('=') {
+                Some(pos) => (Some(&part[..pos]), &part[pos + 1..]),
+                None => (None, part),
+            };
+            match (module, level.parse::<LevelFilter>()) {
+                (Some(module), Ok(level)) => modules.push((module.to_string(), level)),
+                (None, Ok(level)) => default = level,
+                (_, Err(_)) => eprintln!("[{}]\tIgnoring bad log level `{}`", "WARN".red(), part),
+            }
+        }
+        modules.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        TgnewsLogger {
+            default,
+            modules,
+            json,
+        }
+    }
+    /// The level for a log target, targets are module paths by default
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| {
+                target == module
+                    || (target.starts_with(module.as_str())
+                        && target[module.len()..].starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+    /// The most verbose level we'll ever log, so the macros can skip the rest early
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, std::cmp::max)
+    }
+    fn format_json(record: &Record, fields: Vec<(String, Json)>) -> String {
+        let mut map = Map::new();
+        map.insert(
+            "time".to_string(),
+            Json::from(Local::now().to_rfc3339()),
+        );
+        map.insert("level".to_string(), Json::from(record.level().as_str()));
+        map.insert("target".to_string(), Json::from(record.target()));
+        map.insert(
+            "message".to_string(),
+            Json::from(record.args().to_string()),
+        );
+        for (key, value) in fields {
+            map.insert(key, value);
+        }
+        Json::Object(map).to_string()
+    }
+    fn format_text(record: &Record, fields: Vec<(String, Json)>) -> String {
+        let level = match record.level() {
+            Level::Error | Level::Warn => record.level().as_str().red(),
+            Level::Info => record.level().as_str().green(),
+            Level::Debug | Level::Trace => record.level().as_str().blue(),
+        };
+        let mut line = format!(
+            "{} [{}]\t{}\t{}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            level,
+            record.target(),
+            record.args()
+        );
+        for (key, value) in fields {
+            match value {
+                Json::String(s) => line.push_str(&format!(" {}={}", key.cyan(), s)),
+                v => line.push_str(&format!(" {}={}", key.cyan(), v)),
+            }
+        }
+        line
     }
 }
-pub fn tgnews_debug<T>(_message: T)
-where
-    T: AsRef<str>,
-{
+
+/// Collects the key-values of a record
+struct Fields(Vec<(String, Json)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let value = if let Some(v) = value.to_u64() {
+            Json::from(v)
+        } else if let Some(v) = value.to_i64() {
+            Json::from(v)
+        } else if let Some(v) = value.to_f64() {
+            Json::from(v)
+        } else if let Some(v) = value.to_bool() {
+            Json::from(v)
+        } else {
+            Json::from(value.to_string())
+        };
+        self.0.push((key.as_str().to_string(), value));
+        Ok(())
+    }
 }
-pub fn tgnews_warn<T>(message: T)
-where
-    T: AsRef<str>,
-{
-    log_msg(log::Level::Warn, message);
+
+impl Log for TgnewsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut fields = Fields(vec![]);
+        // a bad key-value shouldn't lose the message
+        let _ = record.key_values().visit(&mut fields);
+        let line = if self.json {
+            TgnewsLogger::format_json(record, fields.0)
+        } else {
+            TgnewsLogger::format_text(record, fields.0)
+        };
+        let _ = writeln!(std::io::stderr(), "{}", line);
+    }
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Install the logger, reading its configuration from `TGNEWS_LOG` and `TGNEWS_LOG_FORMAT`
+///
+/// Calling it more than once does nothing
+pub fn init() {
+    let spec = std::env::var("TGNEWS_LOG").unwrap_or_else(|_| DEFAULT_LEVEL.to_string());
+    let json = std::env::var("TGNEWS_LOG_FORMAT")
+        .map(|format| format.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    let logger = TgnewsLogger::from_spec(spec.as_str(), json);
+    let max_level = logger.max_level();
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
 }
@@ -14,16 +14,24 @@ extern crate rocket;
 #[macro_use]
 extern crate log;
 
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches};
+
+use crate::embeddings::Representation;
+use crate::ranking::SortBy;
+use crate::server::transfer::{Filter, Format};
+use crate::slink::TemporalConfig;
 
-pub mod alexa;
 pub mod categories;
 pub mod document;
+pub mod duplicates;
+pub mod embeddings;
 pub mod languages;
 pub mod logger;
 pub mod news;
+pub mod ranking;
 pub mod server;
 pub mod slink;
+pub mod sources;
 pub mod threads;
 pub mod utils;
 // My modules
@@ -33,6 +41,7 @@ pub mod utils;
 /// But is asynchronous when in server mode
 #[rocket::main]
 async fn main() {
+    crate::logger::init();
     // Call it to ensure dbase_conn doesn't try saving data in directories that don't exist
     // Command line arguments
     let app = App::new("tgnews")
@@ -64,12 +73,143 @@ async fn main() {
             ),
         )
         .subcommand(
-            App::new("threads").about("<source dir>").arg(
-                Arg::new("dir")
-                    .takes_value(true)
-                    .about("source dir")
-                    .required(true),
-            ),
+            App::new("threads")
+                .about("<source dir>")
+                .arg(
+                    Arg::new("dir")
+                        .takes_value(true)
+                        .about("source dir")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["trending", "size", "sources", "recency", "importance"])
+                        .about("order of the threads, as found if left out"),
+                )
+                .arg(
+                    Arg::new("ranking")
+                        .long("ranking")
+                        .takes_value(true)
+                        .about("JSON file with the weights of the importance score"),
+                )
+                .arg(
+                    Arg::new("sources")
+                        .long("sources")
+                        .takes_value(true)
+                        .about("JSON file listing the source rating files, the Alexa ratings if left out"),
+                )
+                .arg(
+                    Arg::new("representation")
+                        .long("representation")
+                        .takes_value(true)
+                        .possible_values(&["title", "title_lead", "tfidf"])
+                        .about("what articles are clustered by, their title if left out"),
+                )
+                .arg(
+                    Arg::new("window")
+                        .long("window")
+                        .takes_value(true)
+                        .about("seconds apart after which articles are never in one thread, 0 for no limit, a week if left out"),
+                )
+                .arg(
+                    Arg::new("doubling")
+                        .long("doubling")
+                        .takes_value(true)
+                        .about("seconds apart after which the distance of two articles doubles, off if left out"),
+                )
+                .arg(
+                    Arg::new("collapse")
+                        .long("collapse")
+                        .about("keep one article per site in each thread, listing the rest as duplicates"),
+                ),
+        )
+        .subcommand(
+            App::new("duplicates")
+                .about("Group near duplicate articles in <source dir>")
+                .arg(
+                    Arg::new("dir")
+                        .takes_value(true)
+                        .about("source dir")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            App::new("source")
+                .about("Print the rating a site resolves to")
+                .arg(
+                    Arg::new("host")
+                        .takes_value(true)
+                        .about("host or URL of the site")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("sources")
+                        .long("sources")
+                        .takes_value(true)
+                        .about("JSON file listing the source rating files, the Alexa ratings if left out"),
+                ),
+        )
+        .subcommand(
+            App::new("migrate")
+                .about("Upgrade the server's DataBase to the current schema")
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .about("Report what would change without writing anything"),
+                ),
+        )
+        .subcommand(transfer_args(
+            App::new("export")
+                .about("Dump the server's articles, TTL and threads to a file")
+                .arg(
+                    Arg::new("file")
+                        .takes_value(true)
+                        .about("file to write, - for stdout")
+                        .required(true),
+                ),
+        ))
+        .subcommand(transfer_args(
+            App::new("import")
+                .about("Load articles from an export into the server's DataBase")
+                .arg(
+                    Arg::new("file")
+                        .takes_value(true)
+                        .about("file to read, - for stdin")
+                        .required(true),
+                ),
+        ))
+        .subcommand(
+            App::new("server-ingest")
+                .about("Send a directory of articles to a running server")
+                .arg(
+                    Arg::new("dir")
+                        .takes_value(true)
+                        .about("source dir")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .takes_value(true)
+                        .default_value("http://localhost:8000")
+                        .about("the server"),
+                )
+                .arg(
+                    Arg::new("batch")
+                        .long("batch")
+                        .takes_value(true)
+                        .default_value("500")
+                        .about("files per request"),
+                )
+                .arg(
+                    Arg::new("max-age")
+                        .long("max-age")
+                        .takes_value(true)
+                        .default_value("86400")
+                        .about("seconds to keep each article, as in Cache-Control"),
+                ),
         )
         .subcommand(
             App::new("server").about("<port>").arg(
@@ -108,14 +248,47 @@ async fn main() {
                 .unwrap(),
             thread,
         ),
-        Some("threads") => crate::threads::entry(
+        Some("threads") => {
+            let matches = matches.subcommand_matches("threads").unwrap();
+            if let Some(path) = matches.value_of("ranking") {
+                crate::ranking::configure_from_file(path);
+            }
+            if let Some(path) = matches.value_of("sources") {
+                crate::sources::configure_from_file(path);
+            }
+            if let Some(representation) = matches.value_of("representation") {
+                crate::embeddings::configure(Representation::from_name(representation).unwrap());
+            }
+            let mut temporal = TemporalConfig::default();
+            if let Some(window) = matches.value_of("window") {
+                temporal.window = window.parse().expect("--window should be a number of seconds");
+            }
+            if let Some(doubling) = matches.value_of("doubling") {
+                temporal.doubling = doubling.parse().expect("--doubling should be a number of seconds");
+            }
+            crate::slink::configure_temporal(temporal);
+            crate::threads::entry(
+                matches.value_of("dir").unwrap(),
+                thread,
+                matches.value_of("sort").and_then(SortBy::from_name),
+                matches.is_present("collapse"),
+            )
+        }
+        Some("duplicates") => crate::duplicates::entry(
             matches
-                .subcommand_matches("threads")
+                .subcommand_matches("duplicates")
                 .unwrap()
                 .value_of("dir")
                 .unwrap(),
             thread,
         ),
+        Some("source") => {
+            let matches = matches.subcommand_matches("source").unwrap();
+            if let Some(path) = matches.value_of("sources") {
+                crate::sources::configure_from_file(path);
+            }
+            crate::sources::entry(matches.value_of("host").unwrap())
+        }
         Some("server") => {
             crate::server::mount(
                 matches
@@ -129,8 +302,97 @@ async fn main() {
             )
             .await
         }
+        Some("migrate") => crate::server::schema::migrate_entry(
+            matches
+                .subcommand_matches("migrate")
+                .unwrap()
+                .is_present("dry-run"),
+        ),
+        Some("server-ingest") => {
+            let ingest = matches.subcommand_matches("server-ingest").unwrap();
+            crate::server::bulk::ingest_entry(
+                ingest.value_of("dir").unwrap(),
+                ingest.value_of("url").unwrap(),
+                ingest
+                    .value_of_t("batch")
+                    .expect("Could not convert batch to a number"),
+                ingest
+                    .value_of_t("max-age")
+                    .expect("Could not convert max-age to a number"),
+            )
+        }
+        Some("export") => {
+            let (format, filter) = transfer_options(matches.subcommand_matches("export").unwrap());
+            crate::server::transfer::export_entry(
+                matches
+                    .subcommand_matches("export")
+                    .unwrap()
+                    .value_of("file")
+                    .unwrap(),
+                format,
+                &filter,
+            )
+        }
+        Some("import") => {
+            let (format, filter) = transfer_options(matches.subcommand_matches("import").unwrap());
+            crate::server::transfer::import_entry(
+                matches
+                    .subcommand_matches("import")
+                    .unwrap()
+                    .value_of("file")
+                    .unwrap(),
+                format,
+                &filter,
+            )
+        }
         None => println!("Unknown command, run tgnews -h for available commands"),
         Some(x) => println!("Unknown command '{}'", x),
     }
 }
+/// Options shared by `export` and `import`
+fn transfer_args(app: App<'static>) -> App<'static> {
+    app.arg(
+        Arg::new("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["jsonl", "proto"])
+            .default_value("jsonl")
+            .about("jsonl or length delimited protobuf"),
+    )
+    .arg(
+        Arg::new("lang")
+            .long("lang")
+            .takes_value(true)
+            .about("only articles in this language, en or ru"),
+    )
+    .arg(
+        Arg::new("category")
+            .long("category")
+            .takes_value(true)
+            .about("only articles in this category"),
+    )
+    .arg(
+        Arg::new("since")
+            .long("since")
+            .takes_value(true)
+            .about("only articles published since, unix seconds or YYYY-MM-DD"),
+    )
+    .arg(
+        Arg::new("until")
+            .long("until")
+            .takes_value(true)
+            .about("only articles published until, unix seconds or YYYY-MM-DD(inclusive)"),
+    )
+}
+fn transfer_options(matches: &ArgMatches) -> (Format, Filter) {
+    let format = Format::from_name(matches.value_of("format").unwrap()).unwrap();
+    let filter = Filter::parse(
+        matches.value_of("lang"),
+        matches.value_of("category"),
+        matches.value_of("since"),
+        matches.value_of("until"),
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    (format, filter)
+}
 // Done 🛩
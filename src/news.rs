@@ -26,7 +26,6 @@ use select::predicate::{Attr, Name};
 use serde_json::json;
 use whatlang::{Detector, Lang};
 
-use crate::logger::tgnews_debug;
 use crate::utils::split_files_for_threads;
 use std::fs::read_to_string;
 use std::path::Path;
@@ -205,13 +204,10 @@ pub fn entry(path: &str, thread: usize) {
         }
     })
     .expect("Could not spawn threads");
-    tgnews_debug(format!(
-        "Finished filtering news files in {} seconds",
-        tm.elapsed().as_secs()
-    ));
-    tgnews_debug(format!(
-        "Found {} news articles",
-        news.lock().unwrap().len()
-    ));
+    debug!(
+        duration = tm.elapsed().as_secs_f64(),
+        articles = news.lock().unwrap().len();
+        "Finished filtering news files"
+    );
     format_for_output(&*news.lock().unwrap());
 }
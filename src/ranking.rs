@@ -0,0 +1,371 @@
+//! Scoring and ordering threads
+//!
+//! # Importance
+//! Every thread gets a score, reported with its parts as `rank`
+//! ```text
+//! score = category weight × (authority × a + size × s + sources × d + recency × r + reliability × l)
+//! ```
+//! * `a`, how well known the thread's best source is, its authority in the source registry(see `sources.rs`),
+//!  about 0 to 3
+//! * `s`, `ln(1 + articles)`
+//! * `d`, `ln(1 + distinct hosts)`, ten articles from one site are one source
+//! * `r`, `0.5 ^ (age of the newest article / half_life)`, 1 for the newest thread
+//! * `l`, how reliable the thread's most reliable source has been on the server, 0 to 1(see `source_stats.rs`),
+//!  always 0 for the `threads` command
+//!
+//! and the weights(`authority`, `size`, `sources`, `recency`, `reliability`, `half_life` in seconds and a
+//! weight per category name, 1 if left out) come from a `RankingConfig`. The server reads it from
+//! `[default.ranking]` in `Rocket.toml`, the `threads` command from the JSON file passed with `--ranking`.
+//!
+//! Articles are scored with the same weights to pick a thread's lead article, see `article_importance`.
+//!
+//! # Order
+//! Both the `threads` command and `GET /threads` can sort threads by
+//! * `size`, most articles first(what the server always did)
+//! * `sources`, most distinct hosts first
+//! * `recency`, newest article first
+//! * `importance`, highest score first
+//! * `trending`, threads whose articles are arriving faster than they used to
+//!
+//! The trending score compares how many articles a thread got in the last `WINDOW` with how many it would
+//! have got at its usual pace, the rate between its first article and the start of the window.
+//! A steady thread scores about 1, a thread picking up scores higher. Both counts get one added so a new thread
+//! with three articles doesn't outrank everything, it scores 4.
+//!
+//! "Now" is the publish time of the newest article we know of rather than the clock, for threads and
+//! articles alike, datasets are often from the past
+//!
+//! # Duplicates
+//! Several articles of a thread are often from the same site. Each thread reports its distinct `sources` and
+//! can be collapsed to the first(most important) article of each host, with the rest under `duplicates`
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::categories::enums::Categories;
+use crate::server::cluster::AllArticles;
+use crate::server::source_stats::reliability;
+use crate::slink::{collapse_hosts, Articles};
+use crate::sources::registry;
+
+/// How far back counts as recent, in seconds
+const WINDOW: u64 = 3 * 60 * 60;
+
+lazy_static! {
+    static ref CONFIG: RwLock<RankingConfig> = RwLock::new(RankingConfig::default());
+}
+
+/// Weights of the importance score, e.g
+/// ```toml
+/// [default.ranking]
+/// recency = 4.0
+/// half_life = 43200
+/// categories = { society = 1.5, other = 0.5 }
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub authority: f64,
+    pub size: f64,
+    pub sources: f64,
+    pub recency: f64,
+    /// Weight of the reliability learned from each host's articles, off unless set
+    pub reliability: f64,
+    /// Seconds after which an article counts half as recent
+    pub half_life: u64,
+    /// Multiplies the score of threads in a category, by category name
+    pub categories: HashMap<String, f64>,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        RankingConfig {
+            authority: 1.0,
+            size: 1.0,
+            sources: 1.0,
+            recency: 2.0,
+            reliability: 0.0,
+            half_life: 6 * 60 * 60,
+            categories: HashMap::new(),
+        }
+    }
+}
+
+impl RankingConfig {
+    fn category_weight(&self, category: Categories) -> f64 {
+        self.categories
+            .get(category.to_string().as_str())
+            .copied()
+            .unwrap_or(1.0)
+    }
+    /// `0.5 ^ (age / half_life)`
+    #[allow(clippy::cast_precision_loss)]
+    fn recency(&self, age: u64) -> f64 {
+        0.5_f64.powf(age as f64 / self.half_life.max(1) as f64)
+    }
+}
+
+/// Use these weights from now on
+pub fn configure(config: RankingConfig) {
+    *CONFIG.write().unwrap() = config;
+}
+
+/// Use the weights in a JSON file, for the `threads` command
+///
+/// # Panics
+/// If the file can't be read or isn't a valid `RankingConfig`
+pub fn configure_from_file(path: &str) {
+    let file = read_to_string(path).expect("Could not read ranking configuration");
+    configure(serde_json::from_str(file.as_str()).expect("Invalid ranking configuration"));
+}
+
+/// The weights in use
+pub fn config() -> RankingConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+/// How well known a source is, 0 for hosts we have no rating for, see `SourceRegistry::authority`
+pub fn authority(host: &str) -> f64 {
+    registry().authority(host)
+}
+
+/// How important a single article is, higher is more important
+///
+/// This is the log of `accuracy × (1 + authority × a + reliability × l) × recency ^ recency weight`, logs so
+/// that year old articles don't all end up at 0. `published` is in unix seconds, ages are against `now`
+/// as in `sort_threads`, the newest article we know of
+#[allow(clippy::cast_precision_loss)]
+pub fn article_importance(accuracy: f32, host: &str, published: u64, now: u64) -> f64 {
+    let config = CONFIG.read().unwrap();
+    let age = now.saturating_sub(published) as f64;
+    f64::from(accuracy.max(0.01)).ln()
+        + (config.authority * authority(host) + config.reliability * reliability(host)).ln_1p()
+        - config.recency * 2_f64.ln() * age / config.half_life.max(1) as f64
+}
+
+/// A thread's importance score and what it's made of, see the module docs
+#[derive(Serialize, Clone, Copy, Default, Debug)]
+pub struct Rank {
+    pub score: f64,
+    pub authority: f64,
+    pub size: f64,
+    pub sources: f64,
+    pub recency: f64,
+    pub reliability: f64,
+    pub category_weight: f64,
+}
+
+/// The hosts a thread's articles come from
+fn distinct_hosts<T: Ranked + ?Sized>(thread: &T) -> HashSet<&str> {
+    thread
+        .hosts()
+        .iter()
+        .map(String::as_str)
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+impl Rank {
+    /// Score a thread, `now` as in `sort_threads`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn of<T: Ranked + ?Sized>(thread: &T, config: &RankingConfig, now: u64) -> Rank {
+        let hosts = distinct_hosts(thread);
+        let authority = hosts.iter().map(|host| authority(host)).fold(0.0, f64::max);
+        let reliability = hosts
+            .iter()
+            .map(|host| reliability(host))
+            .fold(0.0, f64::max);
+        let size = (thread.size() as f64).ln_1p();
+        let sources = (hosts.len() as f64).ln_1p();
+        let newest = thread.times().iter().max().copied().unwrap_or(0);
+        let recency = config.recency(now.saturating_sub(newest));
+        let category_weight = config.category_weight(thread.category());
+        Rank {
+            score: category_weight
+                * (config.authority * authority
+                    + config.size * size
+                    + config.sources * sources
+                    + config.recency * recency
+                    + config.reliability * reliability),
+            authority,
+            size,
+            sources,
+            recency,
+            reliability,
+            category_weight,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortBy {
+    Trending,
+    Size,
+    Sources,
+    Recency,
+    Importance,
+}
+
+impl Default for SortBy {
+    fn default() -> Self {
+        SortBy::Size
+    }
+}
+
+impl SortBy {
+    pub fn from_name(name: &str) -> Option<SortBy> {
+        match name {
+            "trending" => Some(SortBy::Trending),
+            "size" => Some(SortBy::Size),
+            "sources" => Some(SortBy::Sources),
+            "recency" => Some(SortBy::Recency),
+            "importance" => Some(SortBy::Importance),
+            _ => None,
+        }
+    }
+}
+
+/// What we need from a thread to rank it
+pub trait Ranked {
+    /// Publish times of the articles, unix seconds
+    fn times(&self) -> &[u64];
+    /// Hosts of the articles, may be shorter than the articles for threads saved before we kept them
+    fn hosts(&self) -> &[String];
+    fn size(&self) -> usize;
+    fn category(&self) -> Categories;
+    fn rank(&self) -> Rank;
+    fn set_rank(&mut self, rank: Rank);
+    fn set_sources(&mut self, sources: usize);
+    /// Keep one article per host, see `slink::collapse_hosts`
+    fn collapse_sources(&mut self);
+}
+
+impl Ranked for Articles {
+    fn times(&self) -> &[u64] {
+        &self.article_times
+    }
+    fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+    fn size(&self) -> usize {
+        self.articles.len()
+    }
+    fn category(&self) -> Categories {
+        self.category
+    }
+    fn rank(&self) -> Rank {
+        self.rank.unwrap_or_default()
+    }
+    fn set_rank(&mut self, rank: Rank) {
+        self.rank = Some(rank);
+    }
+    fn set_sources(&mut self, sources: usize) {
+        self.sources = sources;
+    }
+    fn collapse_sources(&mut self) {
+        self.duplicates = collapse_hosts(&mut self.articles, &mut self.article_times, &mut self.hosts);
+    }
+}
+
+impl Ranked for AllArticles {
+    fn times(&self) -> &[u64] {
+        &self.times
+    }
+    fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+    fn size(&self) -> usize {
+        self.articles.len()
+    }
+    fn category(&self) -> Categories {
+        self.category
+    }
+    fn rank(&self) -> Rank {
+        self.rank.unwrap_or_default()
+    }
+    fn set_rank(&mut self, rank: Rank) {
+        self.rank = Some(rank);
+    }
+    fn set_sources(&mut self, sources: usize) {
+        self.sources = sources;
+    }
+    fn collapse_sources(&mut self) {
+        self.duplicates = collapse_hosts(&mut self.articles, &mut self.times, &mut self.hosts);
+    }
+}
+
+/// How much faster articles arrived in the last `WINDOW` before `now` than before it
+#[allow(clippy::cast_precision_loss)]
+pub fn trending_score(times: &[u64], now: u64) -> f64 {
+    let window_start = now.saturating_sub(WINDOW);
+    let recent = times.iter().filter(|&&t| t >= window_start).count();
+    let first = match times.iter().copied().filter(|&t| t < window_start).min() {
+        Some(first) => first,
+        // everything is recent
+        None => return (recent + 1) as f64,
+    };
+    let earlier = times.len() - recent;
+    // never shorter than a window, or one old article a minute before the window looks like a fast pace
+    let span = (window_start - first).max(WINDOW);
+    let expected = earlier as f64 * WINDOW as f64 / span as f64;
+    (recent + 1) as f64 / (expected + 1.0)
+}
+
+/// Set the importance score and distinct sources of every thread
+pub fn rank_threads<T: Ranked>(threads: &mut [T], now: u64) {
+    let config = config();
+    for thread in threads.iter_mut() {
+        let rank = Rank::of(&*thread, &config, now);
+        let sources = distinct_hosts(&*thread).len();
+        thread.set_rank(rank);
+        thread.set_sources(sources);
+    }
+}
+
+/// Keep one article per host in every thread, the others are listed as its duplicates
+///
+/// Call this after sorting, threads are ranked with all their articles
+pub fn collapse_threads<T: Ranked>(threads: &mut [T]) {
+    threads.iter_mut().for_each(Ranked::collapse_sources);
+}
+
+/// Score threads and sort them, the first one is the top one
+///
+/// Ties are broken by size
+pub fn sort_threads<T: Ranked>(threads: &mut Vec<T>, by: SortBy, now: u64) {
+    rank_threads(threads, now);
+    let by_size = |a: &T, b: &T| b.size().cmp(&a.size());
+    match by {
+        SortBy::Size => threads.sort_by(by_size),
+        SortBy::Sources => {
+            threads.sort_by_cached_key(|t| Reverse((distinct_hosts(t).len(), t.size())))
+        }
+        SortBy::Recency => threads.sort_by(|a, b| {
+            let newest = |t: &T| t.times().iter().max().copied().unwrap_or(0);
+            newest(b).cmp(&newest(a)).then_with(|| by_size(a, b))
+        }),
+        SortBy::Importance => threads.sort_by(|a, b| {
+            b.rank()
+                .score
+                .partial_cmp(&a.rank().score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| by_size(a, b))
+        }),
+        SortBy::Trending => {
+            let mut scored: Vec<(f64, T)> = threads
+                .drain(..)
+                .map(|thread| (trending_score(thread.times(), now), thread))
+                .collect();
+            scored.sort_by(|(a_score, a), (b_score, b)| {
+                b_score
+                    .partial_cmp(a_score)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| by_size(a, b))
+            });
+            threads.extend(scored.into_iter().map(|(_, thread)| thread));
+        }
+    }
+}
@@ -2,21 +2,44 @@ use std::sync::RwLock;
 
 use rocket::config::Config;
 use rocket::http::Status;
-use rocket::logger::LogLevel;
+use rocket::State;
 use sled::{Config as SConfig, Mode};
 use sled::Db;
+use whatlang::Lang;
 
 use cluster::SClusterer;
 
-use crate::server::static_pools::execute_static_pools;
+use crate::embeddings;
+use crate::ranking;
+use crate::server::config::ServerConfig;
+use crate::server::state::ServerState;
+use crate::slink;
+use crate::sources;
 
+pub mod bulk;
 pub mod cluster;
+pub mod config;
 mod delete_article;
 pub mod enums;
+mod events;
 mod fast_text;
+mod get_article;
+mod health;
+mod history;
+pub mod metrics;
 pub mod protos;
+mod request_id;
+pub mod scheduler;
+pub mod schema;
+mod shutdown;
+mod snapshot;
+pub mod source_stats;
+pub mod state;
 mod static_pools;
+pub mod store;
+mod timeline;
 mod top;
+pub mod transfer;
 mod upload;
 lazy_static! {
 
@@ -30,53 +53,96 @@ lazy_static! {
 
     };
     pub static ref EN_CLUSTERS:RwLock<SClusterer>={
-        RwLock::new(SClusterer::new("English"))
+        RwLock::new(SClusterer::new("English", Lang::Eng))
     };
     pub static ref RU_CLUSTERS:RwLock<SClusterer>={
-        RwLock::new(SClusterer::new("Russian"))
+        RwLock::new(SClusterer::new("Russian", Lang::Rus))
     };
 
 }
-/// Whether the database has been rebuilt
-pub static mut FINISHED_REBUILDING: bool = false;
-/// Have English clusters been built
-pub static mut FINISHED_CLUSTERING_EN: bool = false;
-/// Have Russian clusters been built
-pub static mut FINISHED_CLUSTERING_RU: bool = false;
+
+/// Map a `lang_code` as used in requests to a language
+pub fn lang_from_code(code: &str) -> Option<Lang> {
+    match code {
+        "en" => Some(Lang::Eng),
+        "ru" => Some(Lang::Rus),
+        _ => None,
+    }
+}
+/// The `lang_code` of a language we handle, everything that isn't Russian is English
+pub fn lang_code(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Rus => "ru",
+        _ => "en",
+    }
+}
 
 /// Mount the server
 ///
 /// This is the starting point for server part
 pub async fn mount(port: u16) {
-    execute_static_pools();
-
     let config = Config::figment()
         .merge(("port",port)).
         merge(("address","0.0.0.0"))
-        .merge(("log_level","critical"));
-    rocket::custom(config)
+        .merge(("log_level","critical"))
+        // we handle Ctrl-C ourselves, see `shutdown`
+        .merge(("ctrlc",false));
+    let server_config = ServerConfig::from_figment(&config);
+    ranking::configure(server_config.ranking.clone());
+    embeddings::configure(server_config.representation);
+    slink::configure_temporal(server_config.temporal);
+    sources::configure(&server_config.sources);
+    // open the store now, so a bad configuration or schema fails here and not on the first upload
+    store::store();
+    snapshot::load_frequencies();
+    let state = ServerState::new();
+    let scheduler = static_pools::start(state.clone(), &server_config.jobs);
+
+    let rocket = rocket::custom(config)
+        .manage(state.clone())
+        .manage(scheduler.clone())
+        .attach(request_id::RequestIds)
+        .attach(metrics::RequestTimer)
         .mount(
             "/",
             routes![
                 get,
                 upload::upload,
                 upload::malformed_upload,
+                bulk::bulk,
                 delete_article::delete_file,
+                get_article::get_file,
+                get_article::head_file,
                 top::get_top,
-                top::get_all_top
+                top::get_all_top,
+                events::stream,
+                history::get_thread,
+                timeline::get_timeline,
+                source_stats::get_source,
+                health::live,
+                health::ready,
+                metrics::metrics,
+                scheduler::list_jobs,
+                scheduler::trigger_job
             ],
-        )
-        .launch()
-        .await
-        .expect("Aww Snap, server crashed, should have spent more time here\n");
+        );
+    tokio::spawn(shutdown::on_signal(
+        state.clone(),
+        scheduler.clone(),
+        server_config.shutdown_timeout(),
+        rocket.shutdown(),
+    ));
+    let result = rocket.launch().await;
+    // if we got here through a signal the jobs are already stopped and the threads saved, saving again is
+    // cheap
+    shutdown::persist(&state, &scheduler);
+    result.expect("Aww Snap, server crashed, should have spent more time here\n");
 }
 #[get("/")]
-async fn get() -> Status {
-    unsafe {
-        return if FINISHED_REBUILDING {
-            Status::Ok
-        } else {
-            Status::NotImplemented
-        };
+async fn get(state: State<'_, ServerState>) -> Status {
+    if state.is_rebuilt() {
+        Status::Ok
+    } else {
+        Status::NotImplemented
     }
 }
@@ -0,0 +1,245 @@
+//! Uploading many articles in one request, and a client for it
+//!
+//! `POST /bulk` takes newline delimited JSON, one article per line
+//! ```json
+//! {"name": "article.html", "html": "<!DOCTYPE html>...", "max_age": 3600}
+//! ```
+//! `max_age` can be left out when the request has a Cache-Control header, it then applies to every item.
+//! Items go through the same checks as `PUT /<article>`(see `upload::ingest`), a few at a time in parallel,
+//! and the response has a status per item in the order they were sent
+//!
+//! `tgnews server-ingest <dir> --url <server>` sends a directory to a running server this way
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use rocket::data::{ByteUnit, Data};
+use rocket::http::Status;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::server::enums::HTMLData;
+use crate::server::request_id::RequestId;
+use crate::server::state::ServerState;
+use crate::server::top::PrettyJson;
+use crate::server::upload::{ingest, CacheControl, Preconditions};
+
+/// Largest batch we read, larger ones get `413`
+const LIMIT: ByteUnit = ByteUnit::Megabyte(256);
+/// Items checked at once, so one batch doesn't take every blocking thread
+const PARALLEL: usize = 16;
+
+/// A line of a bulk upload
+#[derive(Serialize, Deserialize)]
+pub struct BulkItem {
+    pub name: String,
+    pub html: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+}
+
+/// What happened to one item
+#[derive(Serialize, Deserialize)]
+pub struct ItemStatus {
+    pub name: String,
+    pub status: u16,
+    /// As in the upload metrics, `created`, `not_news`... or `invalid` if the item couldn't be read
+    pub outcome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ItemStatus {
+    fn invalid(name: String, status: Status, error: String) -> ItemStatus {
+        ItemStatus {
+            name,
+            status: status.code,
+            outcome: "invalid".to_string(),
+            etag: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct BulkReport {
+    /// How many items had each outcome
+    pub outcomes: BTreeMap<String, usize>,
+    pub items: Vec<ItemStatus>,
+}
+
+/// Parse and check one line
+fn process(line: &str, max_age: Option<u64>, request_id: RequestId) -> ItemStatus {
+    let item: BulkItem = match serde_json::from_str(line) {
+        Ok(item) => item,
+        Err(e) => return ItemStatus::invalid(String::new(), Status::BadRequest, e.to_string()),
+    };
+    if item.name.is_empty() || item.name.contains('/') {
+        return ItemStatus::invalid(item.name, Status::BadRequest, "Invalid name".to_string());
+    }
+    let max_age = match item.max_age.or(max_age) {
+        Some(max_age) => max_age,
+        None => {
+            return ItemStatus::invalid(
+                item.name,
+                Status::BadRequest,
+                "Missing max_age and no Cache-Control header".to_string(),
+            )
+        }
+    };
+    let html = match HTMLData::from_string(item.html) {
+        Some(html) => html,
+        None => {
+            return ItemStatus::invalid(
+                item.name,
+                Status::UnprocessableEntity,
+                "Could not process entity".to_string(),
+            )
+        }
+    };
+    let uploaded = ingest(
+        item.name.as_str(),
+        html,
+        max_age,
+        &Preconditions::default(),
+        request_id,
+    );
+    ItemStatus {
+        name: item.name,
+        status: uploaded.status().code,
+        outcome: uploaded.name().to_string(),
+        etag: uploaded.etag().map(String::from),
+        error: None,
+    }
+}
+
+/// Upload a batch of articles as newline delimited JSON
+///
+/// Returns `200` with a status per item, the whole request only fails if the server isn't
+/// accepting uploads or the batch is larger than `LIMIT`(`413`)
+#[post("/bulk", data = "<data>")]
+pub async fn bulk(
+    data: Data,
+    cache_control: Option<CacheControl>,
+    request_id: RequestId,
+    state: State<'_, ServerState>,
+) -> Result<PrettyJson<BulkReport>, Status> {
+    state.check_writable()?;
+    let body = data.open(LIMIT).into_string().await.map_err(|e| {
+        error!(request_id = request_id.as_str(); "Could not read bulk upload\n{}", e);
+        Status::InternalServerError
+    })?;
+    if !body.is_complete() {
+        warn!(request_id = request_id.as_str(), limit = LIMIT.as_u64(); "Bulk upload is too large");
+        return Err(Status::PayloadTooLarge);
+    }
+    let body = body.into_inner();
+    let max_age = cache_control.map(|c| c.max_age());
+    let lines: Vec<String> = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect();
+    let time = Instant::now();
+    let mut report = BulkReport::default();
+    for (chunk_number, chunk) in lines.chunks(PARALLEL).enumerate() {
+        let handles = chunk.iter().cloned().enumerate().map(|(i, line)| {
+            let item_id = request_id.item(chunk_number * PARALLEL + i);
+            tokio::task::spawn_blocking(move || process(line.as_str(), max_age, item_id))
+        });
+        for status in join_all(handles).await {
+            let status = status.unwrap_or_else(|e| {
+                ItemStatus::invalid(String::new(), Status::InternalServerError, e.to_string())
+            });
+            *report.outcomes.entry(status.outcome.clone()).or_insert(0) += 1;
+            report.items.push(status);
+        }
+    }
+    info!(
+        request_id = request_id.as_str(),
+        items = report.items.len(),
+        duration = time.elapsed().as_secs_f64();
+        "Processed bulk upload"
+    );
+    Ok(PrettyJson(report))
+}
+
+/// Send a batch, waiting for the server while it isn't accepting uploads
+fn send(url: &str, body: &str) -> Result<BulkReport, String> {
+    const ATTEMPTS: usize = 30;
+    for _ in 0..ATTEMPTS {
+        let response = ureq::post(url)
+            .set("Content-Type", "application/x-ndjson")
+            .send_string(body);
+        if response.status() == Status::ServiceUnavailable.code {
+            warn!("Server is not accepting uploads yet, retrying");
+            thread::sleep(Duration::from_secs(10));
+            continue;
+        }
+        if let Some(e) = response.synthetic_error() {
+            return Err(e.to_string());
+        }
+        if !response.ok() {
+            return Err(format!("Server responded with {}", response.status()));
+        }
+        let body = response.into_string().map_err(|e| e.to_string())?;
+        return serde_json::from_str(body.as_str()).map_err(|e| e.to_string());
+    }
+    Err(format!("Server not accepting uploads after {} attempts", ATTEMPTS))
+}
+
+/// Entry point for `tgnews server-ingest`
+///
+/// Sends every file under `dir` to the server at `url`, `batch` files per request.
+/// Failed items are logged, the counts of every outcome are printed as JSON
+pub fn ingest_entry(dir: &str, url: &str, batch: usize, max_age: u64) {
+    let url = format!("{}/bulk", url.trim_end_matches('/'));
+    let files: Vec<_> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.is_dir())
+        .map(|entry| entry.path().to_path_buf())
+        .collect();
+    info!(files = files.len(), url = url.as_str(); "Sending files");
+    let time = Instant::now();
+    let mut outcomes: BTreeMap<String, usize> = BTreeMap::new();
+    for paths in files.chunks(batch.max(1)) {
+        let mut body = String::new();
+        for path in paths {
+            let html = match std::fs::read(path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                Err(e) => {
+                    warn!(file = path.to_string_lossy().as_ref(); "Could not read file\n{}", e);
+                    *outcomes.entry("unreadable".to_string()).or_insert(0) += 1;
+                    continue;
+                }
+            };
+            let item = BulkItem {
+                name: path.file_name().unwrap().to_string_lossy().to_string(),
+                html,
+                max_age: Some(max_age),
+            };
+            body.push_str(serde_json::to_string(&item).unwrap().as_str());
+            body.push('\n');
+        }
+        let report =
+            send(url.as_str(), body.as_str()).unwrap_or_else(|e| panic!("Bulk upload failed\n{}", e));
+        for item in report.items.iter().filter(|item| item.error.is_some()) {
+            warn!(
+                article = item.name.as_str(),
+                status = item.status;
+                "Upload failed: {}",
+                item.error.as_deref().unwrap_or_default()
+            );
+        }
+        for (outcome, count) in report.outcomes {
+            *outcomes.entry(outcome).or_insert(0) += count;
+        }
+    }
+    info!(duration = time.elapsed().as_secs_f64(); "Sent all files");
+    println!("{}", serde_json::to_string_pretty(&outcomes).unwrap());
+}
@@ -5,7 +5,7 @@
 //! There two functions here:
 //! * `cluster()` is pretty easy,makes sense and won;t drive you mad
 //!  it takes a document , checks if it's category is below thresh-hold(aka 0.45) and if not tries to
-//! add an Alexa rating for it, then adds it to the appropriate cluster and calls `write_proto` to write the file
+//! add its source's rating for it, then adds it to the appropriate cluster and calls `write_proto` to write the file
 //! as a protobuf bytes and sends those bytes to sled, the most amazing key value database store ever created
 //!
 //! * Every other thing doesn't make sense actually, you're better off decoding photoshop PSD's format than doing this
@@ -19,97 +19,129 @@
 //!
 //! Hey you know what, figure out the rest, I can't make this boring, GOOD LUCK
 
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
-use chrono::Local;
-use colored::*;
 use eddie::Levenshtein;
 use ndarray::{arr1, Array1, Array2, ArrayView1};
 use ndarray_stats::QuantileExt;
 use serde::Serialize;
-use url::Url;
 use whatlang::Lang;
 
-use crate::alexa::ALEXA_RATINGS;
 use crate::categories::enums::Categories;
-use crate::server::enums::{HTErr, HTMLData};
-use crate::server::protos::server_files::ProtoFile;
-use crate::server::protos::write_to_dbase;
-use crate::server::{EN_CLUSTERS, GLOBAL_DBASE, RU_CLUSTERS};
-use crate::slink::{cosine, Articles};
-use crate::threads::{EN_VECTORS, RU_VECTORS};
-use crate::utils::clean;
-use protobuf::parse_from_bytes;
+use crate::duplicates::DuplicateIndex;
+use crate::embeddings::representation;
+use crate::ranking::{article_importance, Rank};
+use crate::server::enums::HTMLData;
+use crate::server::events;
+use crate::server::history::{self, HistoryEntry};
+use crate::server::get_article::ThreadInfo;
+use crate::server::protos::server_files::{ClusterSnapshot, Language, Thread};
+use crate::server::metrics::CLUSTERING_SECONDS;
+use crate::server::protos::{category_from_proto, category_to_proto, read_ttl, write_to_dbase};
+use crate::server::request_id::RequestId;
+use crate::server::store::store;
+use crate::server::snapshot;
+use crate::server::state::LanguageProgress;
+use crate::server::timeline;
+use crate::server::{lang_code, EN_CLUSTERS, RU_CLUSTERS};
+use crate::slink::{cosine, temporal, time_span, Articles};
+use crate::sources::registry;
+use crate::utils::host;
 
-const DECAY: f64 = 10_000.0;
 /// Maximum number of files a cluster should have before breaking them when clustering
 const MAX_FILES: usize = 9000;
 
-/// Clusters document getting it's category and it's Alexa rating
+/// Clusters a classified document(see `upload::ingest`), getting it's source rating
 ///
-/// And calling Write-Proto which saves the file
+/// And calling Write-Proto which saves the file, it's dropped instead if another version(or a delete)
+/// took its place since `ingest` reserved it
 ///
 /// This is run in a separate thread from the main one so most heavy computations should be here
-pub async fn cluster(article: HTMLData) {
+///
+/// # Arguments
+/// `replace`: The article already exists, so drop the old version from the clusters
+/// `request_id`: The id of the upload request, attached to our logs
+pub async fn cluster(article: HTMLData, replace: bool, request_id: RequestId) {
     let mut article = article;
-    // If we cannot classify it to a category return early
-
-    if let Err(e) = article.set_category_and_accuracy() {
-        match e {
-            HTErr::NoCategory(f) => {
-                if f != 1.0 {
-                    warn!(
-                        "Categories threshold below normal @  `{:.5}`, dropping article `{}`",
-                        f, article.title
-                    );
-                } else {
-                    warn!(
-                        "Could not determine appropriate category for `{:?}` dropping it",
-                        article.title
-                    )
-                }
-            }
-        }
-        return;
-    }
     // extract url and give it a rating if it exists
-    let url = Url::from_str(article.url.as_str())
-        .unwrap()
-        .host_str()
-        .unwrap()
-        .replace("www.", "");
-    if let Some(alexa_ratings) = ALEXA_RATINGS.get(url.as_str()) {
-        if let Some(rating) = alexa_ratings.get_country("US") {
-            match article.lang.unwrap() {
-                Lang::Eng => article.set_alexa_rating_us(*rating),
-                _ => {}
-            }
-        }
-        if let Some(rating) = alexa_ratings.get_country("RU") {
-            match article.lang.unwrap() {
-                Lang::Rus => article.set_alexa_rating_rus(*rating),
+    let url = host(article.url.as_str()).unwrap_or_default();
+    let sources = registry();
+    if let Some(rating) = sources.rating(url.as_str()) {
+        // the fields kept their Alexa names, they hold the audience share in the language's country
+        let lang = article.lang.unwrap();
+        if let Some(share) = sources.language_share(rating, lang_code(lang)) {
+            match lang {
+                Lang::Eng => article.set_alexa_rating_us(share),
+                Lang::Rus => article.set_alexa_rating_rus(share),
                 _ => {}
             }
         }
-        article.global_rating = alexa_ratings.get_rating().into();
+        article.global_rating = rating.global_rating();
     }
 
+    let lang = article.lang.unwrap();
+    // hold the clusters until the article is stored, so no other version gets clustered in between.
+    // The language may have changed between versions so a replacement looks in both
+    let mut en = if replace || lang != Lang::Rus {
+        Some(EN_CLUSTERS.write().unwrap())
+    } else {
+        None
+    };
+    let mut ru = if replace || lang == Lang::Rus {
+        Some(RU_CLUSTERS.write().unwrap())
+    } else {
+        None
+    };
+    // a newer upload or a delete got in after `ingest` reserved the article, it wins
+    let stored = store().get(&article.file_name).ok().flatten();
+    if stored.map_or(true, |file| file.content_hash != article.content_hash) {
+        debug!(
+            request_id = request_id.as_str(),
+            article = article.file_name.as_str();
+            "Article was replaced or deleted before being stored, dropping it"
+        );
+        return;
+    }
+    if replace {
+        en.iter_mut().for_each(|clusters| {
+            clusters.remove(&article.file_name);
+        });
+        ru.iter_mut().for_each(|clusters| {
+            clusters.remove(&article.file_name);
+        });
+        debug!(
+            request_id = request_id.as_str(),
+            article = article.file_name.as_str();
+            "Replacing existing article"
+        );
+    }
+    let clusters = match lang {
+        Lang::Rus => ru.as_deref_mut().unwrap(),
+        _ => en.as_deref_mut().unwrap(),
+    };
+    if let Some(original) = clusters.original_of(article.simhash) {
+        debug!(
+            request_id = request_id.as_str(),
+            article = article.file_name.as_str(),
+            original = original.as_str();
+            "Article is a near duplicate"
+        );
+        article.duplicate_of = original;
+    }
+    if !write_to_dbase(&article) {
+        debug!(
+            request_id = request_id.as_str(),
+            article = article.file_name.as_str();
+            "Article was replaced or deleted before being stored, dropping it"
+        );
+        return;
+    }
     // add doc to respective cluster
     // note, the doc will not be added to a cluster until either 5 minutes elapses or we get a `GET`
     // request for threads
-    match article.lang.unwrap() {
-        Lang::Eng => {
-            EN_CLUSTERS.write().unwrap().add(&article);
-        }
-        Lang::Rus => {
-            RU_CLUSTERS.write().unwrap().add(&article);
-        }
-        _ => (),
-    }
-    write_to_dbase(&article).await;
+    clusters.add(&article);
 }
 
 #[derive(Default, Clone)]
@@ -119,39 +151,68 @@ pub struct SingleArticle {
     pub decay: i64,
     pub time: u64,
     pub file: String,
+    /// Host of the article's url, empty if it has none
+    pub host: String,
     pub embeddings: Vec<f32>,
 }
 impl SingleArticle {
     /// Construct a single article from a HTML Document
     pub fn from_html(h: &HTMLData) -> SingleArticle {
-        let embeddings = match h.lang.unwrap() {
-            Lang::Eng => EN_VECTORS.get_sentence_vector(clean(h.title.clone(), true).as_str()),
-            Lang::Rus => RU_VECTORS.get_sentence_vector(clean(h.title.clone(), false).as_str()),
-            _ => unreachable!(),
-        };
+        let embeddings = snapshot::embedding(h);
+        let host = host(h.url.as_str()).unwrap_or_default();
+        // against the newest article we have, smaller decay is more important, the opposite of the
+        // importance score
+        let now = read_ttl().unwrap_or(0).max(h.date_published);
+        let decay =
+            -(article_importance(h.accuracy, host.as_str(), h.date_published, now) * 100.0).round();
 
         SingleArticle {
             title: h.title.clone(),
-            decay: h.calc_decay(DECAY).round() as i64,
+            decay: decay as i64,
             category: h.category,
             time: h.date_published,
             file: h.file_name.clone(),
+            host,
             embeddings,
         }
     }
 }
 
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
 #[derive(Default, Serialize, Clone)]
 pub struct AllArticles {
-    title: String,
-    category: Categories,
+    /// Stable across clusterings, see `history.rs`
+    pub(crate) id: String,
+    pub(crate) title: String,
+    pub(crate) category: Categories,
     #[serde(skip)]
     pub decay: i64,
     #[serde(skip)]
     pub embeddings: Array1<f32>,
     #[serde(skip)]
     pub times: Vec<u64>,
+    /// Hosts of the articles, in the same order
+    #[serde(skip)]
+    pub hosts: Vec<String>,
+    /// Publication time of the oldest article, unix seconds
+    pub(crate) first_seen: u64,
+    /// Publication time of the newest article
+    pub(crate) last_updated: u64,
+    /// Importance score and its parts, set when the threads are ranked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<Rank>,
+    /// Distinct hosts of the articles, set when the threads are ranked
+    #[serde(skip_serializing_if = "is_zero")]
+    pub sources: usize,
     pub(crate) articles: Vec<String>,
+    /// Articles from the same host as an article in `articles`, by that article. Only when collapsed
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub duplicates: BTreeMap<String, Vec<String>>,
+    #[serde(skip)]
+    pub history: Vec<HistoryEntry>,
 }
 
 impl AllArticles {
@@ -166,6 +227,7 @@ impl AllArticles {
         let mut articles = vec![files[0].file.clone()];
         let mut embeddings = arr1(files[0].embeddings.as_slice());
         let mut times = vec![files[0].time];
+        let mut hosts = vec![files[0].host.clone()];
         let category = files[0].category;
         let mut decay = 0;
         files.remove(0);
@@ -174,18 +236,134 @@ impl AllArticles {
         articles.extend(files.iter().map(|f| {
             decay += f.decay;
             times.push(f.time);
+            hosts.push(f.host.clone());
             embeddings += &arr1(f.embeddings.as_slice());
             f.file.clone()
         }));
         decay /= ((articles.len() + 1) * (articles.len() + 1)) as i64;
         embeddings /= articles.len() as f32;
+        let (first_seen, last_updated) = time_span(&times);
         AllArticles {
+            id: String::new(),
             title: master,
             category,
             decay,
             times,
+            hosts,
+            first_seen,
+            last_updated,
+            rank: None,
+            sources: 0,
             articles,
+            duplicates: BTreeMap::new(),
             embeddings,
+            history: vec![],
+        }
+    }
+    /// Remove a file from this thread
+    ///
+    /// Returns `None` if the file isn't in the thread, otherwise whether it was the lead article
+    pub fn remove_file(&mut self, file: &str) -> Option<bool> {
+        let pos = self.articles.iter().position(|a| a == file)?;
+        self.articles.remove(pos);
+        self.times.remove(pos);
+        if pos < self.hosts.len() {
+            self.hosts.remove(pos);
+        }
+        let (first_seen, last_updated) = time_span(&self.times);
+        self.first_seen = first_seen;
+        self.last_updated = last_updated;
+        Some(pos == 0)
+    }
+    /// Remove an article from this thread as the clusterer would have left it
+    ///
+    /// Unlike `remove_file` the centroid loses the article's vector and, if it was the lead, the
+    /// remaining article with the lowest decay takes over the title and lead position. The articles
+    /// are looked up in `unclustered`.
+    ///
+    /// Returns `None` if the article isn't in the thread, otherwise whether the thread is stale: the
+    /// lead was removed and its successor isn't known, so it needs re-clustering
+    fn remove_article(
+        &mut self,
+        removed: &SingleArticle,
+        unclustered: &Unclustered,
+    ) -> Option<bool> {
+        let count = self.articles.len();
+        let lead = self.remove_file(removed.file.as_str())?;
+        if self.articles.is_empty() {
+            return Some(false);
+        }
+        if removed.embeddings.len() == self.embeddings.len() {
+            self.embeddings *= count as f32;
+            self.embeddings -= &arr1(removed.embeddings.as_slice());
+            self.embeddings /= (count - 1) as f32;
+        }
+        let remaining: Option<Vec<&SingleArticle>> = self
+            .articles
+            .iter()
+            .map(|file| unclustered.get(file.as_str()))
+            .collect();
+        let remaining = match remaining {
+            Some(remaining) => remaining,
+            None => return Some(lead),
+        };
+        if lead {
+            let (pos, next) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, article)| article.decay)
+                .unwrap();
+            self.title = next.title.clone();
+            let file = self.articles.remove(pos);
+            self.articles.insert(0, file);
+            let time = self.times.remove(pos);
+            self.times.insert(0, time);
+            if pos < self.hosts.len() {
+                let host = self.hosts.remove(pos);
+                self.hosts.insert(0, host);
+            }
+        }
+        // same as `from_single_article`, the lead's decay doesn't count
+        let lead_decay = remaining.iter().map(|a| a.decay).min().unwrap();
+        let decay: i64 = remaining.iter().map(|a| a.decay).sum::<i64>() - lead_decay;
+        self.decay = decay / ((self.articles.len() + 1) * (self.articles.len() + 1)) as i64;
+        Some(false)
+    }
+    fn to_thread(&self) -> Thread {
+        let mut thread = Thread::new();
+        thread.set_title(self.title.clone());
+        thread.set_category(category_to_proto(self.category));
+        thread.set_decay(self.decay);
+        thread.set_articles(self.articles.clone().into());
+        thread.set_times(self.times.iter().map(|&t| t as i64).collect());
+        thread.set_centroid(self.embeddings.to_vec());
+        thread.set_id(self.id.clone());
+        thread.set_history(self.history.iter().map(HistoryEntry::to_proto).collect());
+        thread.set_hosts(self.hosts.clone().into());
+        thread
+    }
+    fn from_thread(thread: Thread) -> AllArticles {
+        let times: Vec<u64> = thread.times.into_iter().map(|t| t as u64).collect();
+        let (first_seen, last_updated) = time_span(&times);
+        AllArticles {
+            id: thread.id,
+            title: thread.title,
+            category: category_from_proto(thread.category),
+            decay: thread.decay,
+            embeddings: Array1::from(thread.centroid),
+            times,
+            hosts: thread.hosts.into_vec(),
+            first_seen,
+            last_updated,
+            rank: None,
+            sources: 0,
+            articles: thread.articles.into_vec(),
+            duplicates: BTreeMap::new(),
+            history: thread
+                .history
+                .into_iter()
+                .map(HistoryEntry::from_proto)
+                .collect(),
         }
     }
     pub fn remove_stale_docs(&self, ttl_time: u64, stale_time: u64) -> Option<AllArticles> {
@@ -203,23 +381,22 @@ impl AllArticles {
         }
         let mut single_articles = Vec::with_capacity(files.len());
         for i in files {
-            match GLOBAL_DBASE.get(i.as_bytes()) {
-                Ok(value) => match value {
-                    None => {}
-                    Some(finally) => {
-                        let proto: ProtoFile = parse_from_bytes(finally.as_ref()).unwrap();
-                        let html = HTMLData::from_proto(proto);
-                        let file = SingleArticle::from_html(&html);
-                        single_articles.push(file);
-                    }
-                },
-                Err(_) => {}
+            if let Ok(Some(proto)) = store().get(i.as_str()) {
+                let html = HTMLData::from_proto(proto);
+                // the sweeper may not have got to it yet
+                if html.is_expired(ttl_time) {
+                    continue;
+                }
+                let file = SingleArticle::from_html(&html);
+                single_articles.push(file);
             }
         }
         if single_articles.is_empty() {
             return None;
         }
-        let articles = AllArticles::from_single_article(single_articles.as_slice());
+        let mut articles = AllArticles::from_single_article(single_articles.as_slice());
+        articles.id = self.id.clone();
+        articles.history = self.history.clone();
         return Some(articles);
     }
 }
@@ -236,6 +413,73 @@ pub struct Docs {
     pub all: Arc<RwLock<Vec<AllArticles>>>,
 }
 
+impl Docs {
+    /// The per category threads along with their category
+    fn categories(&self) -> [(Categories, &Arc<RwLock<Vec<Articles>>>); 7] {
+        [
+            (Categories::Society, &self.society),
+            (Categories::Economy, &self.economy),
+            (Categories::Technology, &self.technology),
+            (Categories::Entertainment, &self.entertainment),
+            (Categories::Sports, &self.sports),
+            (Categories::Science, &self.science),
+            (Categories::Other, &self.other),
+        ]
+    }
+    /// Remove an article from the threads it is in, dropping threads that end up empty
+    ///
+    /// Returns `None` if no thread had the article, otherwise whether any of them is now stale(see
+    /// `AllArticles::remove_article`), along with the threads(across all categories) that were dropped
+    pub fn remove(
+        &self,
+        removed: &SingleArticle,
+        unclustered: &Unclustered,
+    ) -> (Option<bool>, Vec<AllArticles>) {
+        let mut result = None;
+        for (_, category) in self.categories().iter() {
+            let mut threads = category.write().unwrap();
+            for thread in threads.iter_mut() {
+                if let Some(stale) = thread.remove_article(removed, unclustered) {
+                    result = Some(result.unwrap_or(false) || stale);
+                }
+            }
+            threads.retain(|thread| !thread.articles.is_empty());
+        }
+        let mut all = self.all.write().unwrap();
+        for thread in all.iter_mut() {
+            if let Some(stale) = thread.remove_article(removed, unclustered) {
+                result = Some(result.unwrap_or(false) || stale);
+            }
+        }
+        let (emptied, kept) = all.drain(..).partition(|thread| thread.articles.is_empty());
+        *all = kept;
+        (result, emptied)
+    }
+    /// Give the per category threads the ids of the matching threads in `all`
+    ///
+    /// They come from the same clusters, so the thread in `all` holding a category thread's lead
+    /// article has the same articles
+    fn link_categories(&self) {
+        let all = self.all.read().unwrap();
+        let ids: HashMap<&str, &str> = all
+            .iter()
+            .flat_map(|thread| {
+                thread
+                    .articles
+                    .iter()
+                    .map(move |article| (article.as_str(), thread.id.as_str()))
+            })
+            .collect();
+        for (_, threads) in self.categories().iter() {
+            for thread in threads.write().unwrap().iter_mut() {
+                if let Some(id) = thread.articles.first().and_then(|lead| ids.get(lead.as_str())) {
+                    thread.id = id.to_string();
+                }
+            }
+        }
+    }
+}
+
 /// There are two instances of this running,
 /// for en and ru
 pub struct SClusterer {
@@ -243,7 +487,11 @@ pub struct SClusterer {
     pub docs: Docs,
     len: usize,
     name: String,
+    lang: Lang,
     modified: bool,
+    progress: Arc<LanguageProgress>,
+    /// Body hashes of the articles, see `duplicates.rs`
+    near_duplicates: DuplicateIndex,
 }
 
 #[derive(Default)]
@@ -258,6 +506,43 @@ pub struct Unclustered {
 }
 
 impl Unclustered {
+    /// Remove an article from whichever category it is in
+    ///
+    /// Returns the article if it was found
+    pub fn remove(&mut self, file: &str) -> Option<SingleArticle> {
+        let mut found = None;
+        for category in [
+            &mut self.society,
+            &mut self.economy,
+            &mut self.technology,
+            &mut self.entertainment,
+            &mut self.sports,
+            &mut self.science,
+            &mut self.other,
+        ]
+        .iter_mut()
+        {
+            if let Some(pos) = category.iter().position(|a| a.file == file) {
+                found = Some(category.remove(pos));
+            }
+        }
+        found
+    }
+    /// The article with this file name, from whichever category it is in
+    pub fn get(&self, file: &str) -> Option<&SingleArticle> {
+        [
+            &self.society,
+            &self.economy,
+            &self.technology,
+            &self.entertainment,
+            &self.sports,
+            &self.science,
+            &self.other,
+        ]
+        .iter()
+        .flat_map(|category| category.iter())
+        .find(|a| a.file == file)
+    }
     pub fn push(&mut self, article: &HTMLData) {
         match article.category {
             Categories::Sports => {
@@ -290,42 +575,85 @@ impl SClusterer {
     /// Add an article to the Cluster
     pub fn add(&mut self, article: &HTMLData) {
         self.len += 1;
+        self.progress.set_articles(self.len);
         self.unclustered.push(&article);
+        self.near_duplicates
+            .insert(article.file_name.as_str(), article.simhash, article.date_published);
         self.modified = true;
     }
-    pub fn new(name: &str) -> SClusterer {
+    /// Remove an article from the cluster
+    ///
+    /// Unlike `add` this takes effect immediately, the article is dropped from the threads it was in
+    /// without re-clustering: their centroids lose its vector and if it led a thread the next article
+    /// takes over. Only if that article isn't known do we re-cluster on the next request
+    pub fn remove(&mut self, file: &str) -> bool {
+        self.near_duplicates.remove(file);
+        let removed = match self.unclustered.remove(file) {
+            Some(removed) => removed,
+            None => return false,
+        };
+        self.len -= 1;
+        self.progress.set_articles(self.len);
+        let (stale, emptied) = self.docs.remove(&removed, &self.unclustered);
+        if let Some(true) = stale {
+            self.modified = true;
+        }
+        emptied
+            .iter()
+            .for_each(|thread| timeline::expire(thread.id.as_str()));
+        events::publish_expired(self.lang, &emptied);
+        self.progress.set_clusters(self.docs.all.read().unwrap().len());
+        true
+    }
+    pub fn new(name: &str, lang: Lang) -> SClusterer {
         SClusterer {
             docs: Docs::default(),
             unclustered: Unclustered::default(),
             len: 0,
             name: String::from(name),
+            lang,
             modified: false,
+            progress: Arc::new(LanguageProgress::default()),
+            near_duplicates: DuplicateIndex::new(),
         }
     }
+    /// The oldest article a body with this hash is a near duplicate of
+    pub fn original_of(&self, simhash: u64) -> Option<String> {
+        self.near_duplicates.original_of(simhash)
+    }
+    /// Articles that are near duplicates of an older article
+    pub fn duplicates(&self) -> HashSet<String> {
+        self.near_duplicates.originals().keys().cloned().collect()
+    }
+    /// Progress of this clusterer, readable without taking its lock
+    pub fn progress(&self) -> Arc<LanguageProgress> {
+        self.progress.clone()
+    }
+    /// Forget every article, to add them again from the DataBase
+    ///
+    /// The threads of the last clustering are kept, the next clustering links its threads to them(see
+    /// `history::link`) and they're served until then
     pub fn flush(&mut self) {
         self.len = 0;
-        self.docs = Docs::default();
         self.modified = false;
         self.unclustered = Unclustered::default();
+        self.near_duplicates = DuplicateIndex::new();
+        self.progress.set_articles(0);
     }
     pub fn get_stats(&self) {
-        let time_now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        println!("LANGUAGE:\t{}", self.name.green());
-        eprintln!("Time now: {}", time_now.blue());
-        eprintln!("Total files: {}", self.len.to_string().red());
-        eprintln!("Clusters in each category:");
-        eprintln!("\tSociety:{}\n\tEconomy:{}\n\tEntertainment:{}\n\tSports:{}\n\tTechnology:{}\n\tScience:{}\n\tOther:{}",
-                  self.docs.society.read().unwrap().len(), self.docs.economy.read().unwrap().len(),
-                  self.docs.entertainment.read().unwrap().len(),
-                  self.docs.sports.read().unwrap().len(), self.docs.technology.read().unwrap().len(),
-                  self.docs.science.read().unwrap().len(),
-                  self.docs.other.read().unwrap().len()
-        );
-        eprintln!(
-            "Database Size on disk: {} bytes\n\n",
-            GLOBAL_DBASE
-                .size_on_disk()
-                .expect("Could not determine size")
+        info!(
+            lang = lang_code(self.lang),
+            language = self.name.as_str(),
+            articles = self.len,
+            society = self.docs.society.read().unwrap().len(),
+            economy = self.docs.economy.read().unwrap().len(),
+            entertainment = self.docs.entertainment.read().unwrap().len(),
+            sports = self.docs.sports.read().unwrap().len(),
+            technology = self.docs.technology.read().unwrap().len(),
+            science = self.docs.science.read().unwrap().len(),
+            other = self.docs.other.read().unwrap().len(),
+            dbase_bytes = store().size_on_disk().unwrap_or(0);
+            "Cluster statistics"
         );
     }
     /// Cluster documents in the DataBase
@@ -335,6 +663,9 @@ impl SClusterer {
             return;
         }
         let time = Instant::now();
+        // anything stored after this is missing from the snapshot, making it out of date
+        let generation = snapshot::generation();
+        let previous = self.docs.all.read().unwrap().clone();
         const SMALL_CUTOFF: f32 = 0.12;
         const LARGE_CUTOFF: f32 = 0.15;
         self.docs = Docs::default();
@@ -394,12 +725,123 @@ impl SClusterer {
             });
         })
         .expect("Could not start clustering");
-        println!(
-            "TIME elapsed while clustering articles in {} : {}",
-            self.name,
-            time.elapsed().as_secs().to_string().blue()
+        info!(
+            lang = lang_code(self.lang),
+            duration = time.elapsed().as_secs_f64();
+            "Finished clustering articles"
+        );
+        CLUSTERING_SECONDS
+            .with_label_values(&[lang_code(self.lang)])
+            .observe(time.elapsed().as_secs_f64());
+        self.progress.set_clusters(self.docs.all.read().unwrap().len());
+        self.modified = false;
+        let changes = history::link(self.lang, &previous, &mut self.docs.all.write().unwrap());
+        self.docs.link_categories();
+        events::publish(changes);
+        snapshot::save(self.lang, &self.snapshot(generation));
+    }
+    /// Save the threads for the next start, clustering first if articles changed since the last clustering
+    pub fn save_snapshot(&mut self) {
+        if self.modified {
+            // saves them too
+            self.cluster();
+        } else {
+            snapshot::save(self.lang, &self.snapshot(snapshot::generation()));
+        }
+    }
+    /// The threads from the last clustering, to be saved with `snapshot::save`
+    pub fn snapshot(&self, generation: u64) -> ClusterSnapshot {
+        let mut snapshot = ClusterSnapshot::new();
+        snapshot.set_generation(generation);
+        snapshot.set_language(match self.lang {
+            Lang::Rus => Language::Rus,
+            _ => Language::Eng,
+        });
+        snapshot.set_articles(self.len as u64);
+        let temporal = temporal();
+        snapshot.set_representation(representation().name().to_string());
+        snapshot.set_window(temporal.window);
+        snapshot.set_doubling(temporal.doubling);
+        snapshot.set_all(
+            self.docs
+                .all
+                .read()
+                .unwrap()
+                .iter()
+                .map(AllArticles::to_thread)
+                .collect(),
         );
+        for (category, threads) in self.docs.categories().iter() {
+            snapshot.mut_categories().extend(
+                threads
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .map(|thread| articles_to_thread(thread, *category)),
+            );
+        }
+        snapshot
+    }
+    /// Use threads from a snapshot instead of clustering
+    ///
+    /// The articles must have been added already. Returns `false` if the snapshot is from another
+    /// generation, doesn't have as many articles as we do or was built with another representation or
+    /// `TemporalConfig`, then it only seeds the threads the next clustering links against(if we have
+    /// none yet) so thread ids carry over
+    pub fn restore(&mut self, snapshot: ClusterSnapshot) -> bool {
+        let mut snapshot = snapshot;
+        let temporal = temporal();
+        let current = snapshot::generation();
+        let usable = if snapshot.representation != representation().name()
+            || snapshot.window != temporal.window
+            || snapshot.doubling != temporal.doubling
+        {
+            info!(
+                lang = lang_code(self.lang),
+                representation = snapshot.representation.as_str(),
+                window = snapshot.window,
+                doubling = snapshot.doubling;
+                "Cluster snapshot was built another way"
+            );
+            false
+        } else if snapshot.generation != current || snapshot.articles != self.len as u64 {
+            info!(
+                lang = lang_code(self.lang),
+                snapshot = snapshot.generation,
+                current = current;
+                "Cluster snapshot is out of date"
+            );
+            false
+        } else {
+            true
+        };
+        if !usable {
+            let mut all = self.docs.all.write().unwrap();
+            if all.is_empty() {
+                *all = snapshot
+                    .take_all()
+                    .into_iter()
+                    .map(AllArticles::from_thread)
+                    .collect();
+            }
+            return false;
+        }
+        let docs = Docs::default();
+        for thread in snapshot.take_categories().into_iter() {
+            let category = category_from_proto(thread.category);
+            if let Some((_, threads)) = docs.categories().iter().find(|(c, _)| *c == category) {
+                threads.write().unwrap().push(articles_from_thread(thread));
+            }
+        }
+        *docs.all.write().unwrap() = snapshot
+            .take_all()
+            .into_iter()
+            .map(AllArticles::from_thread)
+            .collect();
+        self.docs = docs;
         self.modified = false;
+        self.progress.set_clusters(self.docs.all.read().unwrap().len());
+        true
     }
     pub fn get_docs(&mut self) -> Docs {
         if self.modified {
@@ -413,6 +855,76 @@ impl SClusterer {
         }
         self.docs.all.clone().read().unwrap().to_vec()
     }
+    /// Number of articles and threads(from the last clustering) in each category
+    #[rustfmt::skip]
+    pub fn category_sizes(&self) -> Vec<(Categories, usize, usize)> {
+        vec![
+            (Categories::Society, self.unclustered.society.len(), self.docs.society.read().unwrap().len()),
+            (Categories::Economy, self.unclustered.economy.len(), self.docs.economy.read().unwrap().len()),
+            (Categories::Technology, self.unclustered.technology.len(), self.docs.technology.read().unwrap().len()),
+            (Categories::Entertainment, self.unclustered.entertainment.len(), self.docs.entertainment.read().unwrap().len()),
+            (Categories::Sports, self.unclustered.sports.len(), self.docs.sports.read().unwrap().len()),
+            (Categories::Science, self.unclustered.science.len(), self.docs.science.read().unwrap().len()),
+            (Categories::Other, self.unclustered.other.len(), self.docs.other.read().unwrap().len()),
+        ]
+    }
+    /// A thread from the last clustering by its id
+    pub fn thread(&self, id: &str) -> Option<AllArticles> {
+        self.docs
+            .all
+            .read()
+            .unwrap()
+            .iter()
+            .find(|thread| thread.id == id)
+            .cloned()
+    }
+    /// Find the thread containing `file` from the last clustering
+    ///
+    /// This doesn't re-cluster, so articles added after the last clustering return `None`
+    pub fn find_thread(&self, file: &str) -> Option<ThreadInfo> {
+        self.docs
+            .all
+            .read()
+            .unwrap()
+            .iter()
+            .find(|thread| thread.articles.iter().any(|a| a == file))
+            .map(|thread| ThreadInfo {
+                id: thread.id.clone(),
+                title: thread.title.clone(),
+                size: thread.articles.len(),
+            })
+    }
+}
+
+fn articles_to_thread(articles: &Articles, category: Categories) -> Thread {
+    let mut thread = Thread::new();
+    thread.set_title(articles.title.clone());
+    thread.set_category(category_to_proto(category));
+    thread.set_decay(articles.decay);
+    thread.set_articles(articles.articles.clone().into());
+    thread.set_times(articles.article_times.iter().map(|&t| t as i64).collect());
+    thread.set_id(articles.id.clone());
+    thread.set_hosts(articles.hosts.clone().into());
+    thread
+}
+fn articles_from_thread(thread: Thread) -> Articles {
+    let article_times: Vec<u64> = thread.times.into_iter().map(|t| t as u64).collect();
+    let (first_seen, last_updated) = time_span(&article_times);
+    Articles {
+        id: thread.id,
+        title: thread.title,
+        decay: thread.decay,
+        article_times,
+        hosts: thread.hosts.into_vec(),
+        category: category_from_proto(thread.category),
+        first_seen,
+        last_updated,
+        rank: None,
+        sources: 0,
+        articles: thread.articles.into_vec(),
+        duplicates: BTreeMap::new(),
+        duplicate_of: BTreeMap::new(),
+    }
 }
 
 pub fn cluster_articles(
@@ -565,6 +1077,7 @@ fn cluster_single(
 pub fn build_dissimilarity(values: &[SingleArticle]) -> (Array2<f32>, Array1<usize>) {
     let mut dissimilarity_matrix = Array2::<f32>::zeros((values.len(), values.len()));
     let mut closest_cluster = Array1::<usize>::ones(values.len());
+    let temporal = temporal();
     for (i, first) in values.iter().enumerate() {
         for (j, second) in values.iter().enumerate() {
             if i == j {
@@ -572,7 +1085,7 @@ pub fn build_dissimilarity(values: &[SingleArticle]) -> (Array2<f32>, Array1<usi
                 dissimilarity_matrix[[i, j]] = 0.0;
             } else {
                 let e = 1.0 - cosine(first.embeddings.as_slice(), second.embeddings.as_slice());
-                dissimilarity_matrix[[i, j]] = e;
+                dissimilarity_matrix[[i, j]] = temporal.dissimilarity(e, first.time, second.time);
             }
             // Store the index with the lowest dissimilarity to X
             // also ensure i is not equal to j
@@ -0,0 +1,163 @@
+//! Our own settings, read from the same places as Rocket's
+//!
+//! That's `Rocket.toml`(under the active profile) and `ROCKET_` environment variables, e.g
+//! ```toml
+//! [default.jobs.recluster]
+//! interval = 300
+//! jitter = 60
+//! ```
+//! or `ROCKET_JOBS='{recluster={interval=300}}'`. Anything not set keeps its default
+use std::time::Duration;
+
+use rocket::figment::Figment;
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::Representation;
+use crate::ranking::RankingConfig;
+use crate::slink::TemporalConfig;
+use crate::sources::SourceConfig;
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub jobs: JobsConfig,
+    pub store: StoreConfig,
+    /// Weights of the thread importance score, see `ranking.rs`
+    pub ranking: RankingConfig,
+    /// What articles are clustered by, see `embeddings.rs`
+    pub representation: Representation,
+    /// How publication time weighs on clustering, see `slink::TemporalConfig`
+    pub temporal: TemporalConfig,
+    /// Where source ratings come from, see `sources.rs`
+    pub sources: SourceConfig,
+    /// Seconds to wait for uploads still being classified when shutting down
+    pub shutdown_timeout: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            jobs: JobsConfig::default(),
+            store: StoreConfig::default(),
+            ranking: RankingConfig::default(),
+            representation: Representation::default(),
+            temporal: TemporalConfig::default(),
+            sources: SourceConfig::default(),
+            shutdown_timeout: 30,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Extract our settings from Rocket's figment
+    ///
+    /// # Panics
+    /// If a setting is present but invalid, better to refuse starting than run with something we
+    /// weren't asked for
+    pub fn from_figment(figment: &Figment) -> ServerConfig {
+        figment
+            .extract()
+            .unwrap_or_else(|e| panic!("Invalid server configuration\n{}", e))
+    }
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout)
+    }
+}
+
+/// Which database articles are kept in, see `store.rs`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Sled,
+    Memory,
+    Sqlite,
+}
+
+impl Backend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Sled => "sled",
+            Backend::Memory => "memory",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// e.g
+/// ```toml
+/// [default.store]
+/// backend = "sqlite"
+/// sqlite_path = "./server/articles.sqlite"
+/// ```
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StoreConfig {
+    pub backend: Backend,
+    /// Where the `sqlite` backend keeps its file
+    pub sqlite_path: String,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig {
+            backend: Backend::Sled,
+            sqlite_path: "./server/articles.sqlite".to_string(),
+        }
+    }
+}
+
+/// How often a background job runs
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct JobConfig {
+    /// Seconds between runs
+    pub interval: u64,
+    /// Up to this many seconds are randomly added to each interval so jobs don't all fire together
+    pub jitter: u64,
+    /// A disabled job only runs when triggered manually
+    pub enabled: bool,
+}
+
+impl JobConfig {
+    const fn every(interval: u64) -> JobConfig {
+        JobConfig {
+            interval,
+            jitter: 0,
+            enabled: true,
+        }
+    }
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        JobConfig::every(60)
+    }
+}
+
+/// The background jobs, see `static_pools::start` for what each does
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    pub flush_dirty_io: JobConfig,
+    pub remove_stale_docs: JobConfig,
+    pub recluster: JobConfig,
+    pub stats_en: JobConfig,
+    pub stats_ru: JobConfig,
+    pub prune_timelines: JobConfig,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        JobsConfig {
+            flush_dirty_io: JobConfig::every(300),
+            remove_stale_docs: JobConfig::every(60),
+            recluster: JobConfig {
+                jitter: 120,
+                ..JobConfig::every(600)
+            },
+            stats_en: JobConfig::every(60),
+            stats_ru: JobConfig::every(70),
+            prune_timelines: JobConfig::every(3600),
+        }
+    }
+}
@@ -1,29 +1,50 @@
-use crate::server::{
-    FINISHED_CLUSTERING_EN, FINISHED_CLUSTERING_RU, FINISHED_REBUILDING, GLOBAL_DBASE,
-};
+use crate::server::protos::server_files::Language;
+use crate::server::request_id::RequestId;
+use crate::server::snapshot;
+use crate::server::state::ServerState;
+use crate::server::store::{store, StoreError};
+use crate::server::{EN_CLUSTERS, RU_CLUSTERS};
 use rocket::http;
 use rocket::http::Status;
+use rocket::State;
 
 /// Delete an article from the index
 #[delete("/<article>")]
-pub async fn delete_file(article: &http::RawStr) -> Status {
-    unsafe {
-        if !FINISHED_REBUILDING {
-            warn!("Files not rebuilt, cannot process requests");
-            return Status::ServiceUnavailable;
-        } else if !FINISHED_CLUSTERING_EN && !FINISHED_CLUSTERING_RU {
-            warn!("Either English Clustering or Russian clustering not finished, sending not implemented");
-            return Status::ServiceUnavailable;
-        }
+pub async fn delete_file(
+    article: &http::RawStr,
+    request_id: RequestId,
+    state: State<'_, ServerState>,
+) -> Status {
+    if let Err(status) = state.check_writable() {
+        return status;
     }
 
-    let delete_lock = &GLOBAL_DBASE;
-    match delete_lock.remove(article.as_bytes()) {
-        // If there article exists return  NoContent, otherwise return NotFound
-        Ok(result) => match result {
-            Some(_) => http::Status::NoContent,
-            None => http::Status::NotFound,
-        },
-        Err(_) => http::Status::NotFound,
+    // If there article exists return  NoContent, otherwise return NotFound
+    let language = match store().delete(article.as_str()) {
+        Ok(Some(file)) => Some(file.language),
+        // it was there, we just can't tell which language it was in
+        Err(StoreError::Corrupt(_)) => None,
+        Ok(None) | Err(StoreError::Backend(_)) => return http::Status::NotFound,
+    };
+    // drop it from the threads now, rather than waiting for the next rebuild
+    match language {
+        Some(Language::Eng) => {
+            EN_CLUSTERS.write().unwrap().remove(article.as_str());
+        }
+        Some(Language::Rus) => {
+            RU_CLUSTERS.write().unwrap().remove(article.as_str());
+        }
+        None => {
+            EN_CLUSTERS.write().unwrap().remove(article.as_str());
+            RU_CLUSTERS.write().unwrap().remove(article.as_str());
+        }
     }
+    snapshot::forget(article.as_str());
+    snapshot::bump_generation();
+    info!(
+        request_id = request_id.as_str(),
+        article = article.as_str();
+        "Deleted article"
+    );
+    http::Status::NoContent
 }
@@ -1,6 +1,9 @@
 use crate::categories::enums::Categories;
-use crate::server::protos::server_files::{Category, Language, ProtoFile};
-use crate::utils::clean;
+use crate::duplicates::simhash;
+use crate::server::protos::category_from_proto;
+use crate::server::protos::server_files::{Language, ProtoFile};
+use crate::server::schema;
+use crate::utils::{clean, content_hash};
 use select::document::Document;
 use select::predicate::{Attr, Name};
 use std::convert::TryInto;
@@ -20,6 +23,17 @@ pub struct HTMLData {
     pub global_rating: f64,
     pub accuracy: f32,
     pub body: String,
+    /// Hash of the raw HTML the article was created from, sent to clients as an ETag
+    pub content_hash: String,
+    /// How long the article lives after it's published, in seconds
+    ///
+    /// `None` means we don't know(articles stored before we started recording it), `Some(0)` that it
+    /// expires right away
+    pub max_age: Option<u64>,
+    /// SimHash of the cleaned body, see `duplicates.rs`
+    pub simhash: u64,
+    /// The article this one is a near duplicate of, empty if none
+    pub duplicate_of: String,
 }
 pub enum HTErr {
     NoCategory(f32),
@@ -31,6 +45,9 @@ impl HTMLData {
     ///
     /// Current formula tries to remove mainly documents with low ratings, low accuracy and a large time passed since published
     ///
+    /// This only decides when articles without a `max_age` expire, how important an article is comes from
+    /// `ranking::article_importance`
+    ///
     /// # Parameters
     /// `div`: This is the number to divide the current time with time elapsed since article published time to prevent overflows
     pub fn calc_decay(&self, div: f64) -> f64 {
@@ -56,7 +73,20 @@ impl HTMLData {
                 * self.alexa_rating_rus)
                 + 1.0)
     }
+    /// Check whether the article has expired
+    ///
+    /// An article expires once `max_age` seconds have passed between its publishing time and `newest`,
+    /// the publish time of the newest article we have.
+    ///
+    /// Articles without a `max_age` fall back to the decay heuristic
+    pub fn is_expired(&self, newest: u64) -> bool {
+        match self.max_age {
+            Some(max_age) => newest.saturating_sub(self.date_published) >= max_age,
+            None => self.calc_decay(10_000.) > 5.0,
+        }
+    }
     pub fn from_string(string: String) -> Option<HTMLData> {
+        let hash = content_hash(string.as_str());
         let doc = Document::from(string.as_str());
         let url = doc
             .find(Attr("property", "og:url"))
@@ -78,6 +108,7 @@ impl HTMLData {
         let mut body = String::with_capacity(1000);
         doc.find(Name("p")).for_each(|f| body.push_str(&f.text()));
         let body = clean(body, false);
+        let simhash = simhash(body.as_str());
         Some(HTMLData {
             title,
             url,
@@ -92,12 +123,19 @@ impl HTMLData {
             global_rating: 0.1,
             body,
             lang: None,
+            content_hash: hash,
+            // will be set later, from the Cache-Control header
+            max_age: None,
+            simhash,
+            // will be set later, once we know which articles we have
+            duplicate_of: String::new(),
         })
     }
     /// Recover an instance of a HTML document from a Proto file
     /// Note that the body is set to " "since the body isn't saved( since we only need it
     /// for categorization) all, other info is recovered
     pub fn from_proto(file: ProtoFile) -> HTMLData {
+        let max_age = schema::max_age(&file);
         HTMLData {
             title: file.title,
             url: file.url,
@@ -109,22 +147,16 @@ impl HTMLData {
                     Language::Rus => Lang::Rus,
                 })
             },
-            category: {
-                match file.category {
-                    Category::Society => Categories::Society,
-                    Category::Economy => Categories::Economy,
-                    Category::Technology => Categories::Technology,
-                    Category::Entertainment => Categories::Entertainment,
-                    Category::Sports => Categories::Sports,
-                    Category::Science => Categories::Science,
-                    Category::Other => Categories::Other,
-                }
-            },
+            category: category_from_proto(file.category),
             alexa_rating_us: file.us_rating as f64,
             alexa_rating_rus: file.ru_rating.into(),
             global_rating: file.gb_rating.into(),
             accuracy: file.accuracy,
             body: "".to_string(),
+            content_hash: file.content_hash,
+            max_age,
+            simhash: file.simhash,
+            duplicate_of: file.duplicate_of,
         }
     }
     /// Set language
@@ -134,6 +166,10 @@ impl HTMLData {
     pub fn set_file_name(&mut self, lang: String) {
         self.file_name = lang
     }
+    /// The ETag we send to clients for this article
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.content_hash)
+    }
     pub fn set_alexa_rating_us(&mut self, rating: f64) {
         self.alexa_rating_us = rating
     }
@@ -0,0 +1,240 @@
+//! Thread updates pushed to clients as Server-Sent Events
+//!
+//! `GET /threads/stream?lang_code=en&category=society`(both optional) keeps the connection open and sends
+//! ```text
+//! event: grew
+//! data: {"kind":"grew","id":"en-5f0c…","lang_code":"en","category":"society","title":"...","articles":[...],"added":2}
+//! ```
+//! whenever a thread is `created`, `grew`, got `retitled` or `expired`. Events come from comparing the
+//! threads before and after every clustering(see `SClusterer::cluster`), so clients see changes as soon as
+//! the `recluster` job or a `/threads` request re-clusters, without polling for them.
+//! Threads dropped because their last article was deleted or expired are sent right away.
+//!
+//! Which thread is which across clusterings is worked out in `history.rs`
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use rocket::http::ContentType;
+use rocket::http::Status;
+use rocket::response::{self, Responder};
+use rocket::{Request, Response, State};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::{interval, Interval};
+use whatlang::Lang;
+
+use crate::categories::enums::Categories;
+use crate::server::cluster::AllArticles;
+use crate::server::state::{Lifecycle, ServerState};
+use crate::server::{lang_code, lang_from_code};
+
+/// Events buffered per client, a client that falls further behind misses events
+const BUFFER: usize = 256;
+/// Comment lines sent when nothing happened, so proxies don't close idle connections
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Grew,
+    Retitled,
+    Expired,
+}
+
+impl EventKind {
+    fn name(self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Grew => "grew",
+            EventKind::Retitled => "retitled",
+            EventKind::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ThreadEvent {
+    pub kind: EventKind,
+    /// The thread's stable id, see `history.rs`
+    pub id: String,
+    pub lang_code: &'static str,
+    pub category: Categories,
+    pub title: String,
+    /// The title before a `retitled` event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_title: Option<String>,
+    /// The thread's articles, the lead article first
+    pub articles: Vec<String>,
+    /// Articles the thread gained, for `grew`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+}
+
+impl ThreadEvent {
+    pub fn new(kind: EventKind, lang: Lang, thread: &AllArticles) -> ThreadEvent {
+        ThreadEvent {
+            kind,
+            id: thread.id.clone(),
+            lang_code: lang_code(lang),
+            category: thread.category,
+            title: thread.title.clone(),
+            previous_title: None,
+            articles: thread.articles.clone(),
+            added: None,
+        }
+    }
+    /// The event in the `text/event-stream` format
+    fn to_sse(&self) -> String {
+        format!(
+            "event: {}\ndata: {}\n\n",
+            self.kind.name(),
+            serde_json::to_string(self).unwrap()
+        )
+    }
+}
+
+struct Subscriber {
+    lang: Option<Lang>,
+    category: Option<Categories>,
+    sender: mpsc::Sender<Arc<ThreadEvent>>,
+}
+
+impl Subscriber {
+    fn wants(&self, event: &ThreadEvent) -> bool {
+        self.lang.map_or(true, |l| lang_code(l) == event.lang_code)
+            && self.category.map_or(true, |c| c == event.category)
+    }
+}
+
+lazy_static! {
+    static ref SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(vec![]);
+}
+
+/// Send events to every interested client, forgetting clients that went away
+pub fn publish(events: Vec<ThreadEvent>) {
+    if events.is_empty() {
+        return;
+    }
+    let mut subscribers = SUBSCRIBERS.lock().unwrap();
+    if subscribers.is_empty() {
+        return;
+    }
+    debug!(events = events.len(), clients = subscribers.len(); "Publishing thread events");
+    for event in events.into_iter().map(Arc::new) {
+        let mut open = Vec::with_capacity(subscribers.len());
+        for mut subscriber in subscribers.drain(..) {
+            if subscriber.wants(&event) {
+                match subscriber.sender.try_send(event.clone()) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        warn!(kind = event.kind.name(); "Client is not keeping up, dropping thread event")
+                    }
+                    Err(TrySendError::Closed(_)) => continue,
+                }
+            }
+            open.push(subscriber);
+        }
+        *subscribers = open;
+    }
+}
+
+/// Publish threads that lost their last article
+pub fn publish_expired(lang: Lang, threads: &[AllArticles]) {
+    publish(
+        threads
+            .iter()
+            .map(|thread| ThreadEvent::new(EventKind::Expired, lang, thread))
+            .collect(),
+    );
+}
+
+/// End every stream, called when shutting down so open connections don't hold the server up
+pub fn close_all() {
+    SUBSCRIBERS.lock().unwrap().clear();
+}
+
+/// The body of an event stream, events as they are published with keep-alive comments in between
+pub struct EventStream {
+    events: mpsc::Receiver<Arc<ThreadEvent>>,
+    keep_alive: Interval,
+    pending: Vec<u8>,
+}
+
+impl EventStream {
+    fn subscribe(lang: Option<Lang>, category: Option<Categories>) -> EventStream {
+        let (sender, events) = mpsc::channel(BUFFER);
+        SUBSCRIBERS.lock().unwrap().push(Subscriber {
+            lang,
+            category,
+            sender,
+        });
+        EventStream {
+            events,
+            keep_alive: interval(KEEP_ALIVE),
+            // tells EventSource clients how long to wait before reconnecting
+            pending: b"retry: 5000\n\n".to_vec(),
+        }
+    }
+}
+
+impl AsyncRead for EventStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.pending.is_empty() {
+            match this.events.poll_recv(cx) {
+                Poll::Ready(Some(event)) => this.pending = event.to_sse().into_bytes(),
+                // closed, end the response
+                Poll::Ready(None) => return Poll::Ready(Ok(0)),
+                Poll::Pending => match this.keep_alive.poll_tick(cx) {
+                    Poll::Ready(_) => this.pending = b": keep-alive\n\n".to_vec(),
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+        let length = buf.len().min(this.pending.len());
+        buf[..length].copy_from_slice(&this.pending[..length]);
+        this.pending.drain(..length);
+        Poll::Ready(Ok(length))
+    }
+}
+
+impl<'a> Responder<'a, 'static> for EventStream {
+    fn respond_to(self, _: &'a Request<'_>) -> response::Result<'static> {
+        Ok(Response::build()
+            .header(ContentType::new("text", "event-stream"))
+            .raw_header("Cache-Control", "no-cache")
+            .streamed_body(self)
+            .finalize())
+    }
+}
+
+/// Stream thread events, optionally only those of one language and category
+#[get("/threads/stream?<lang_code>&<category>")]
+pub async fn stream(
+    lang_code: Option<String>,
+    category: Option<String>,
+    state: State<'_, ServerState>,
+) -> Result<EventStream, Status> {
+    let lang = match lang_code {
+        Some(code) => Some(lang_from_code(code.as_str()).ok_or(Status::BadRequest)?),
+        None => None,
+    };
+    let category = match category {
+        Some(name) if name == "any" => None,
+        Some(name) => Some(Categories::from_name(name.as_str()).ok_or(Status::BadRequest)?),
+        None => None,
+    };
+    if state.lifecycle() == Lifecycle::Draining {
+        return Err(Status::ServiceUnavailable);
+    }
+    Ok(EventStream::subscribe(lang, category))
+}
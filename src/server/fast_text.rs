@@ -1,6 +1,5 @@
 #![allow(dead_code)]
 use fasttext::{Args, FastText, ModelName};
-use rocket::logger::error;
 use std::fs::write;
 use std::thread::sleep;
 use std::time::Duration;
@@ -27,14 +26,14 @@ pub async fn train_en() {
         match ft.train(&args) {
             Ok(_) => match ft.save_model("./server/en_vectors") {
                 Ok(_) => {}
-                Err(e) => error(format!("Could not save fast-text model \n{}", e).as_str()),
+                Err(e) => error!("Could not save fast-text model \n{}", e),
             },
-            Err(e) => error(format!("FastText English model error \n{}", e).as_str()),
+            Err(e) => error!("FastText English model error \n{}", e),
         }
         // Clean everything after train
         // Clean everything after train
         if let Err(e) = write("./server/en_articles.txt", b"") {
-            error(format!("could not clean en_articles.txt file  \n {}", e).as_str())
+            error!("could not clean en_articles.txt file  \n {}", e)
         }
     }
 }
@@ -60,13 +59,13 @@ pub async fn train_ru() {
         match ft.train(&args) {
             Ok(_) => match ft.save_model("./server/ru_vectors") {
                 Ok(_) => {}
-                Err(e) => error(format!("Could not save fast-text Russian model \n{}", e).as_str()),
+                Err(e) => error!("Could not save fast-text Russian model \n{}", e),
             },
-            Err(e) => error(format!("FastText Russian model error \n{}", e).as_str()),
+            Err(e) => error!("FastText Russian model error \n{}", e),
         }
         // Clean everything after train
         if let Err(e) = write("./server/ru_articles.txt", b"") {
-            error(format!("could not clean ru-articles.txt file  \n {}", e).as_str())
+            error!("could not clean ru-articles.txt file  \n {}", e)
         }
     }
 }
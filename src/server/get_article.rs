@@ -0,0 +1,118 @@
+//! Read back what we stored for an article
+//!
+//! `GET /<article>` returns the decoded proto as JSON along with the thread the article currently
+//! sits in, `HEAD /<article>` only tells you whether we have it
+use rocket::http;
+use rocket::http::Status;
+use rocket::State;
+use serde::Serialize;
+use whatlang::Lang;
+
+use crate::categories::enums::Categories;
+use crate::server::enums::HTMLData;
+use crate::server::top::PrettyJson;
+use crate::server::state::ServerState;
+use crate::server::store::store;
+use crate::server::{lang_code, EN_CLUSTERS, RU_CLUSTERS};
+
+/// The JSON representation of a stored `ProtoFile`
+#[derive(Serialize)]
+pub struct StoredArticle {
+    pub file_name: String,
+    pub title: String,
+    pub url: String,
+    pub date_published: u64,
+    pub lang_code: String,
+    pub category: Categories,
+    pub accuracy: f32,
+    pub global_rating: f64,
+    pub us_rating: f64,
+    pub ru_rating: f64,
+    pub etag: String,
+    /// The article this one was a near duplicate of when it was uploaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+impl StoredArticle {
+    pub fn from_html(html: &HTMLData) -> StoredArticle {
+        StoredArticle {
+            file_name: html.file_name.clone(),
+            title: html.title.clone(),
+            url: html.url.clone(),
+            date_published: html.date_published,
+            lang_code: lang_code(html.lang.unwrap_or(Lang::Eng)).to_string(),
+            category: html.category,
+            accuracy: html.accuracy,
+            global_rating: html.global_rating,
+            us_rating: html.alexa_rating_us,
+            ru_rating: html.alexa_rating_rus,
+            etag: html.etag(),
+            duplicate_of: if html.duplicate_of.is_empty() {
+                None
+            } else {
+                Some(html.duplicate_of.clone())
+            },
+        }
+    }
+}
+
+/// The thread an article was placed in during the last clustering
+#[derive(Serialize)]
+pub struct ThreadInfo {
+    /// Stable id of the thread, see `GET /threads/<id>`
+    pub id: String,
+    pub title: String,
+    pub size: usize,
+}
+
+#[derive(Serialize)]
+pub struct ArticleInfo {
+    #[serde(flatten)]
+    article: StoredArticle,
+    /// `None` if the article hasn't been clustered yet
+    thread: Option<ThreadInfo>,
+}
+
+/// Fetch an article from the DataBase and find the thread it belongs to
+#[get("/<article>")]
+pub async fn get_file(
+    article: &http::RawStr,
+    state: State<'_, ServerState>,
+) -> Result<PrettyJson<ArticleInfo>, Status> {
+    if !state.is_rebuilt() {
+        warn!("Files not rebuilt, cannot process requests");
+        return Err(Status::ServiceUnavailable);
+    }
+    let proto = match store().get(article.as_str()) {
+        Ok(Some(proto)) => proto,
+        Ok(None) => return Err(Status::NotFound),
+        Err(e) => {
+            error!("Could not read `{}` from DBASE\n{}", article, e);
+            return Err(Status::InternalServerError);
+        }
+    };
+    let html = HTMLData::from_proto(proto);
+    // don't force a clustering here, if the article came in after the last one it has no thread yet
+    let thread = match html.lang {
+        Some(Lang::Rus) => RU_CLUSTERS.read().unwrap().find_thread(&html.file_name),
+        _ => EN_CLUSTERS.read().unwrap().find_thread(&html.file_name),
+    };
+    Ok(PrettyJson(ArticleInfo {
+        article: StoredArticle::from_html(&html),
+        thread,
+    }))
+}
+
+/// Check whether an article exists
+#[head("/<article>")]
+pub async fn head_file(article: &http::RawStr, state: State<'_, ServerState>) -> Status {
+    if !state.is_rebuilt() {
+        return Status::ServiceUnavailable;
+    }
+    match store().contains(article.as_str()) {
+        Ok(true) => Status::Ok,
+        Ok(false) => Status::NotFound,
+        Err(_) => Status::InternalServerError,
+    }
+}
@@ -0,0 +1,55 @@
+//! Liveness and readiness probes
+//!
+//! `/health/live` answers as long as the server is up, `/health/ready` only once both languages
+//! have been clustered, with how far each language has got
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::State;
+use serde::Serialize;
+use whatlang::Lang;
+
+use crate::server::state::{Lifecycle, ProgressReport, ServerState};
+use crate::server::top::PrettyJson;
+
+#[derive(Serialize)]
+pub struct Liveness {
+    status: &'static str,
+    lifecycle: Lifecycle,
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    ready: bool,
+    lifecycle: Lifecycle,
+    en: ProgressReport,
+    ru: ProgressReport,
+}
+
+#[get("/health/live")]
+pub async fn live(state: State<'_, ServerState>) -> PrettyJson<Liveness> {
+    PrettyJson(Liveness {
+        status: "live",
+        lifecycle: state.lifecycle(),
+    })
+}
+
+/// Returns `200` when the server is ready and `503` otherwise, the body is the same for both
+#[get("/health/ready")]
+pub async fn ready(state: State<'_, ServerState>) -> Custom<PrettyJson<Readiness>> {
+    let lifecycle = state.lifecycle();
+    let ready = lifecycle == Lifecycle::Ready;
+    let status = if ready {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+    Custom(
+        status,
+        PrettyJson(Readiness {
+            ready,
+            lifecycle,
+            en: state.progress(Lang::Eng).report(),
+            ru: state.progress(Lang::Rus).report(),
+        }),
+    )
+}
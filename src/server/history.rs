@@ -0,0 +1,272 @@
+//! Stable thread ids and what happened to each thread
+//!
+//! Every clustering builds the threads from scratch, so after it we match them to the threads of the
+//! previous one. A thread continues the previous thread it shares the most articles with, failing that
+//! the one in the same category with the closest centroid. It takes over that thread's id and history,
+//! and gets a history entry if it grew or changed title. Threads that continue nothing get a new id
+//!
+//! Ids look like `en-<16 hex digits>`, the hash of the lead article a thread was first seen with,
+//! so they also mostly survive restarts where the saved threads couldn't be used.
+//! `GET /threads/<id>` returns the current articles and history of a thread
+use std::collections::{HashMap, HashSet};
+use std::time::UNIX_EPOCH;
+
+use rocket::http::Status;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+use whatlang::Lang;
+
+use crate::categories::enums::Categories;
+use crate::server::cluster::AllArticles;
+use crate::server::events::{EventKind, ThreadEvent};
+use crate::server::protos::server_files::{ChangeKind, ThreadChange};
+use crate::server::source_stats;
+use crate::server::state::ServerState;
+use crate::server::timeline;
+use crate::server::top::PrettyJson;
+use crate::server::{lang_code, lang_from_code, EN_CLUSTERS, RU_CLUSTERS};
+use crate::slink::cosine;
+use crate::utils::content_hash;
+
+/// Entries kept per thread, older ones are dropped
+const MAX_HISTORY: usize = 100;
+/// Least cosine similarity of centroids for a thread to continue one it shares no articles with
+const CENTROID_MATCH: f32 = 0.95;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    /// Unix seconds
+    pub time: u64,
+    pub kind: EventKind,
+    /// The title after the change
+    pub title: String,
+    /// Articles in the thread after the change
+    pub articles: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+}
+
+impl HistoryEntry {
+    pub fn to_proto(&self) -> ThreadChange {
+        let mut change = ThreadChange::new();
+        change.set_time(self.time);
+        change.set_kind(match self.kind {
+            EventKind::Grew => ChangeKind::Grew,
+            EventKind::Retitled => ChangeKind::Retitled,
+            _ => ChangeKind::Created,
+        });
+        change.set_title(self.title.clone());
+        change.set_articles(self.articles as u32);
+        change.set_added(self.added.unwrap_or(0) as u32);
+        change
+    }
+    pub fn from_proto(change: ThreadChange) -> HistoryEntry {
+        let kind = match change.kind {
+            ChangeKind::Created => EventKind::Created,
+            ChangeKind::Grew => EventKind::Grew,
+            ChangeKind::Retitled => EventKind::Retitled,
+        };
+        HistoryEntry {
+            time: change.time,
+            kind,
+            title: change.title,
+            articles: change.articles as usize,
+            added: if kind == EventKind::Grew {
+                Some(change.added as usize)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// For every thread in `current`, the index of the thread in `previous` it continues, if any
+///
+/// When a thread split, the part with the most shared articles continues it and the rest are new
+fn match_threads(previous: &[AllArticles], current: &[AllArticles]) -> Vec<Option<usize>> {
+    let mut owner: HashMap<&str, usize> = HashMap::new();
+    for (index, thread) in previous.iter().enumerate() {
+        for article in thread.articles.iter() {
+            owner.insert(article.as_str(), index);
+        }
+    }
+    // (shared articles, current, previous)
+    let mut candidates = vec![];
+    for (index, thread) in current.iter().enumerate() {
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for article in thread.articles.iter() {
+            if let Some(&old) = owner.get(article.as_str()) {
+                *shared.entry(old).or_insert(0) += 1;
+            }
+        }
+        if let Some((&old, &count)) = shared.iter().max_by_key(|&(old, count)| (*count, *old)) {
+            candidates.push((count, index, old));
+        }
+    }
+    candidates.sort_unstable_by(|a, b| b.cmp(a));
+    let mut matches = vec![None; current.len()];
+    let mut taken = vec![false; previous.len()];
+    for (_, index, old) in candidates {
+        if !taken[old] {
+            taken[old] = true;
+            matches[index] = Some(old);
+        }
+    }
+    // every article of these is new, try the centroids
+    for (index, thread) in current.iter().enumerate() {
+        if matches[index].is_some() || thread.embeddings.is_empty() {
+            continue;
+        }
+        let closest = previous
+            .iter()
+            .enumerate()
+            .filter(|(old, candidate)| {
+                !taken[*old]
+                    && candidate.category == thread.category
+                    && candidate.embeddings.len() == thread.embeddings.len()
+            })
+            .map(|(old, candidate)| {
+                let similarity = cosine(
+                    thread.embeddings.as_slice().unwrap(),
+                    candidate.embeddings.as_slice().unwrap(),
+                );
+                (old, similarity)
+            })
+            .filter(|(_, similarity)| *similarity >= CENTROID_MATCH)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        if let Some((old, _)) = closest {
+            taken[old] = true;
+            matches[index] = Some(old);
+        }
+    }
+    matches
+}
+
+/// An id for a new thread, not in `used`
+fn new_id(lang: Lang, thread: &AllArticles, used: &HashSet<String>) -> String {
+    let mut seed = thread.articles[0].clone();
+    loop {
+        let id = format!("{}-{}", lang_code(lang), content_hash(seed.as_str()));
+        if !used.contains(&id) {
+            return id;
+        }
+        seed.push('+');
+    }
+}
+
+fn record(thread: &mut AllArticles, time: u64, kind: EventKind, added: Option<usize>) {
+    thread.history.push(HistoryEntry {
+        time,
+        kind,
+        title: thread.title.clone(),
+        articles: thread.articles.len(),
+        added,
+    });
+    if thread.history.len() > MAX_HISTORY {
+        let excess = thread.history.len() - MAX_HISTORY;
+        thread.history.drain(..excess);
+    }
+}
+
+/// Give the threads of a new clustering their ids and history, returns what changed
+pub fn link(lang: Lang, previous: &[AllArticles], current: &mut [AllArticles]) -> Vec<ThreadEvent> {
+    let now = UNIX_EPOCH.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    let matches = match_threads(previous, current);
+    let mut continued = vec![false; previous.len()];
+    let mut used = HashSet::new();
+    let mut events = vec![];
+    // continued threads first, so new ids can't take theirs
+    for (thread, old) in current.iter_mut().zip(matches.iter()) {
+        let old = match old {
+            Some(old) if !previous[*old].id.is_empty() => *old,
+            _ => continue,
+        };
+        continued[old] = true;
+        let old = &previous[old];
+        thread.id = old.id.clone();
+        thread.history = old.history.clone();
+        used.insert(thread.id.clone());
+        let added = thread
+            .articles
+            .iter()
+            .filter(|article| !old.articles.contains(article))
+            .count();
+        if added > 0 {
+            record(thread, now, EventKind::Grew, Some(added));
+            let mut event = ThreadEvent::new(EventKind::Grew, lang, thread);
+            event.added = Some(added);
+            events.push(event);
+        }
+        if old.title != thread.title {
+            record(thread, now, EventKind::Retitled, None);
+            let mut event = ThreadEvent::new(EventKind::Retitled, lang, thread);
+            event.previous_title = Some(old.title.clone());
+            events.push(event);
+        }
+    }
+    for (thread, old) in current.iter_mut().zip(matches.iter()) {
+        if !thread.id.is_empty() {
+            continue;
+        }
+        thread.id = new_id(lang, thread, &used);
+        used.insert(thread.id.clone());
+        match old {
+            // saved before threads had ids, don't call it new
+            Some(old) => {
+                continued[*old] = true;
+                thread.history = previous[*old].history.clone();
+            }
+            None => {
+                record(thread, now, EventKind::Created, None);
+                events.push(ThreadEvent::new(EventKind::Created, lang, thread));
+            }
+        }
+    }
+    for (thread, _) in previous.iter().zip(continued).filter(|(_, c)| !c) {
+        events.push(ThreadEvent::new(EventKind::Expired, lang, thread));
+    }
+    timeline::update(lang, current);
+    source_stats::update(lang, current);
+    events
+}
+
+#[derive(Serialize)]
+pub struct ThreadDetail {
+    pub id: String,
+    pub lang_code: &'static str,
+    pub title: String,
+    pub category: Categories,
+    /// The lead article first
+    pub articles: Vec<String>,
+    /// Oldest first
+    pub history: Vec<HistoryEntry>,
+}
+
+/// A thread from the last clustering, with its history
+///
+/// This doesn't re-cluster, `404` means the thread expired or merged into another one
+#[get("/threads/<id>")]
+pub async fn get_thread(
+    id: String,
+    state: State<'_, ServerState>,
+) -> Result<PrettyJson<ThreadDetail>, Status> {
+    let lang = id
+        .split('-')
+        .next()
+        .and_then(lang_from_code)
+        .ok_or(Status::NotFound)?;
+    state.check_readable(lang)?;
+    let thread = match lang {
+        Lang::Rus => RU_CLUSTERS.read().unwrap().thread(id.as_str()),
+        _ => EN_CLUSTERS.read().unwrap().thread(id.as_str()),
+    };
+    let thread = thread.ok_or(Status::NotFound)?;
+    Ok(PrettyJson(ThreadDetail {
+        id: thread.id,
+        lang_code: lang_code(lang),
+        title: thread.title,
+        category: thread.category,
+        articles: thread.articles,
+        history: thread.history,
+    }))
+}
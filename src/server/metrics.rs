@@ -0,0 +1,135 @@
+//! Prometheus metrics for the server
+//!
+//! Counters and histograms are updated where things happen, gauges(article and thread counts, DBASE size)
+//! are read from the clusters when `/metrics` is scraped
+use std::time::Instant;
+
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Status;
+use rocket::response::content;
+use rocket::{Data, Request, Response};
+
+use crate::server::store::store;
+use crate::server::{EN_CLUSTERS, RU_CLUSTERS};
+
+lazy_static! {
+    /// Uploads by what happened to them
+    ///
+    /// One of `created`, `updated`, `unchanged`, `not_news`, `wrong_language`, `no_category`,
+    /// `expired` or `precondition_failed`
+    pub static ref UPLOADS: IntCounterVec = register_int_counter_vec!(
+        "tgnews_uploads_total",
+        "Uploaded articles by outcome",
+        &["outcome"]
+    )
+    .unwrap();
+    /// How long `SClusterer::cluster` takes per language
+    pub static ref CLUSTERING_SECONDS: HistogramVec = register_histogram_vec!(
+        "tgnews_clustering_duration_seconds",
+        "Time spent clustering all articles of a language",
+        &["lang"],
+        vec![0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]
+    )
+    .unwrap();
+    static ref REQUEST_SECONDS: HistogramVec = register_histogram_vec!(
+        "tgnews_request_duration_seconds",
+        "Time spent handling requests per route",
+        &["route", "method", "status"]
+    )
+    .unwrap();
+    static ref ARTICLES: IntGaugeVec = register_int_gauge_vec!(
+        "tgnews_articles",
+        "Articles in the clusters",
+        &["lang", "category"]
+    )
+    .unwrap();
+    static ref THREADS: IntGaugeVec = register_int_gauge_vec!(
+        "tgnews_threads",
+        "Threads built in the last clustering",
+        &["lang", "category"]
+    )
+    .unwrap();
+    static ref DBASE_BYTES: IntGauge =
+        register_int_gauge!("tgnews_dbase_size_bytes", "Size of the sled DataBase on disk").unwrap();
+}
+
+/// Count an upload outcome
+pub fn upload(outcome: &str) {
+    UPLOADS.with_label_values(&[outcome]).inc();
+}
+
+/// Refresh the gauges from the clusters
+///
+/// If a language is being clustered we don't wait for it, its gauges keep their previous values
+fn refresh_gauges() {
+    for &(code, clusters) in [("en", &*EN_CLUSTERS), ("ru", &*RU_CLUSTERS)].iter() {
+        if let Ok(clusters) = clusters.try_read() {
+            for (category, articles, threads) in clusters.category_sizes() {
+                let category = category.to_string();
+                ARTICLES
+                    .with_label_values(&[code, category.as_str()])
+                    .set(articles as i64);
+                THREADS
+                    .with_label_values(&[code, category.as_str()])
+                    .set(threads as i64);
+            }
+        }
+    }
+    if let Some(size) = store().size_on_disk() {
+        DBASE_BYTES.set(size as i64);
+    }
+}
+
+/// Metrics in Prometheus' text format
+#[get("/metrics")]
+pub async fn metrics() -> Result<content::Plain<String>, Status> {
+    refresh_gauges();
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(|e| {
+            error!("Could not encode metrics\n{}", e);
+            Status::InternalServerError
+        })?;
+    String::from_utf8(buffer)
+        .map(content::Plain)
+        .map_err(|_| Status::InternalServerError)
+}
+
+/// When a request started, stored in the request's local cache
+struct RequestStart(Option<Instant>);
+
+/// A fairing recording how long each route takes
+pub struct RequestTimer;
+
+#[rocket::async_trait]
+impl Fairing for RequestTimer {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request timer",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if let RequestStart(Some(start)) = request.local_cache(|| RequestStart(None)) {
+            let route = request
+                .route()
+                .and_then(|route| route.name)
+                .unwrap_or("unmatched");
+            REQUEST_SECONDS
+                .with_label_values(&[
+                    route,
+                    request.method().as_str(),
+                    response.status().code.to_string().as_str(),
+                ])
+                .observe(start.elapsed().as_secs_f64());
+        }
+    }
+}
@@ -2,13 +2,10 @@
 //!
 //! To write to a DataBase back-end
 //!
-//! Only that,
-//!
-//! Ps its async
+//! Only that
 use std::convert::TryInto;
 use std::str::FromStr;
 
-use protobuf::Message;
 use url::Url;
 use whatlang::Lang;
 
@@ -18,11 +15,37 @@ use server_files::ProtoFile;
 
 use crate::categories::enums::Categories;
 use crate::server::enums::HTMLData;
-use crate::server::GLOBAL_DBASE;
+use crate::server::lang_code;
+use crate::server::schema::SCHEMA_VERSION;
+use crate::server::snapshot;
+use crate::server::store::store;
 
 pub mod server_files;
-/// Write a file to the database
-pub async fn write_to_dbase(article: &HTMLData) {
+/// Write a file to the database, unless another version replaced(or a delete removed) the one
+/// `upload::ingest` reserved for it
+///
+/// Returns whether it was written
+pub fn write_to_dbase(article: &HTMLData) -> bool {
+    let file = to_proto(article);
+    // Also update global time in the DBASE to be the one with the most recent article
+    if !store()
+        .put_if(&file, Some(article.content_hash.as_str()))
+        .expect("Could not add value to DBASE")
+    {
+        return false;
+    }
+    store().update_ttl(article.date_published);
+    snapshot::bump_generation();
+    debug!(
+        article = article.file_name.as_str(),
+        lang = lang_code(article.lang.unwrap_or(Lang::Eng)),
+        category = article.category.to_string().as_str();
+        "Article written to DBASE"
+    );
+    true
+}
+/// The record we keep for an article
+pub fn to_proto(article: &HTMLData) -> ProtoFile {
     let parsed_url = Url::from_str(article.url.as_str())
         .unwrap()
         .domain()
@@ -40,16 +63,7 @@ pub async fn write_to_dbase(article: &HTMLData) {
             .expect("Could not convert date to an i64, seems its below 1970"),
     );
     // Match categories
-    file.set_category(match article.category {
-        Categories::Society => Category::Society,
-        Categories::Economy => Category::Economy,
-        Categories::Sports => Category::Sports,
-        Categories::Entertainment => Category::Entertainment,
-        Categories::Technology => Category::Technology,
-        Categories::Science => Category::Science,
-        Categories::Other => Category::Other,
-        Categories::Unknown => unreachable!(),
-    });
+    file.set_category(category_to_proto(article.category));
     // Languages
     file.set_language(match article.lang {
         Some(Lang::Eng) => Language::Eng,
@@ -60,38 +74,48 @@ pub async fn write_to_dbase(article: &HTMLData) {
     file.set_ru_rating(article.alexa_rating_rus as f32);
     file.gb_rating = article.global_rating as f32;
     file.set_url(parsed_url.clone().replace("www.", ""));
-    // Acquire lock to prevent concurrent writes which is
-    // Also update global time in the DBASE to be the one with the most recent article
-    let x = &GLOBAL_DBASE;
-    x.insert(article.file_name.as_bytes(), file.write_to_bytes().unwrap())
-        .expect("Could not add value to DBASE");
-    update_ttl(article.date_published);
-    debug!("Article `{}` written to DBASE", article.file_name);
+    file.set_content_hash(article.content_hash.clone());
+    file.set_max_age(
+        article
+            .max_age
+            .map_or(-1, |max_age| max_age.try_into().unwrap_or(i64::MAX)),
+    );
+    file.set_simhash(article.simhash);
+    file.set_duplicate_of(article.duplicate_of.clone());
+    file.set_version(SCHEMA_VERSION);
+    file
+}
+/// Map a category to its proto version
+///
+/// # Panics
+/// On `Categories::Unknown`, those never make it to the DataBase
+pub fn category_to_proto(category: Categories) -> Category {
+    match category {
+        Categories::Society => Category::Society,
+        Categories::Economy => Category::Economy,
+        Categories::Sports => Category::Sports,
+        Categories::Entertainment => Category::Entertainment,
+        Categories::Technology => Category::Technology,
+        Categories::Science => Category::Science,
+        Categories::Other => Category::Other,
+        Categories::Unknown => unreachable!(),
+    }
+}
+/// Map a proto category back
+pub fn category_from_proto(category: Category) -> Categories {
+    match category {
+        Category::Society => Categories::Society,
+        Category::Economy => Categories::Economy,
+        Category::Technology => Categories::Technology,
+        Category::Entertainment => Categories::Entertainment,
+        Category::Sports => Categories::Sports,
+        Category::Science => Categories::Science,
+        Category::Other => Categories::Other,
+    }
 }
-/// Update the time to live when a new article arrives
-fn update_ttl(new_time: u64) {
-    let value = match GLOBAL_DBASE.get(b"TTL") {
-        Ok(ttl) => match ttl {
-            None => {
-                warn!("TTL value was none, defaulting to zero");
-                0
-            }
-            Some(time) => {
-                let time: [u8; 8] = time.as_ref().try_into().unwrap();
-                let old_time = u64::from_be_bytes(time);
-                if new_time > old_time {
-                    new_time
-                } else {
-                    old_time
-                }
-            }
-        },
-        Err(e) => {
-            error!("Global TTL was not found, defaulting to 0\n{}", e);
-            0
-        }
-    };
-    GLOBAL_DBASE
-        .insert(b"TTL", value.to_be_bytes().as_ref())
-        .expect("Could not update time to Live for article");
+/// Read the time to live, i.e the publish time of the newest article in the DataBase
+///
+/// Returns `None` if no article has been stored yet
+pub fn read_ttl() -> Option<u64> {
+    store().ttl()
 }
@@ -36,6 +36,11 @@ pub struct ProtoFile {
     pub gb_rating: f32,
     pub ru_rating: f32,
     pub us_rating: f32,
+    pub content_hash: ::std::string::String,
+    pub max_age: i64,
+    pub version: u32,
+    pub simhash: u64,
+    pub duplicate_of: ::std::string::String,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -234,6 +239,103 @@ impl ProtoFile {
     pub fn set_us_rating(&mut self, v: f32) {
         self.us_rating = v;
     }
+
+    // string content_hash = 11;
+
+
+    pub fn get_content_hash(&self) -> &str {
+        &self.content_hash
+    }
+    pub fn clear_content_hash(&mut self) {
+        self.content_hash.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_content_hash(&mut self, v: ::std::string::String) {
+        self.content_hash = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_content_hash(&mut self) -> &mut ::std::string::String {
+        &mut self.content_hash
+    }
+
+    // Take field
+    pub fn take_content_hash(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.content_hash, ::std::string::String::new())
+    }
+
+    // int64 max_age = 12;
+
+
+    pub fn get_max_age(&self) -> i64 {
+        self.max_age
+    }
+    pub fn clear_max_age(&mut self) {
+        self.max_age = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_age(&mut self, v: i64) {
+        self.max_age = v;
+    }
+
+    // uint32 version = 13;
+
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+    pub fn clear_version(&mut self) {
+        self.version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_version(&mut self, v: u32) {
+        self.version = v;
+    }
+
+    // uint64 simhash = 14;
+
+
+    pub fn get_simhash(&self) -> u64 {
+        self.simhash
+    }
+    pub fn clear_simhash(&mut self) {
+        self.simhash = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_simhash(&mut self, v: u64) {
+        self.simhash = v;
+    }
+
+    // string duplicate_of = 15;
+
+
+    pub fn get_duplicate_of(&self) -> &str {
+        &self.duplicate_of
+    }
+    pub fn clear_duplicate_of(&mut self) {
+        self.duplicate_of.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_duplicate_of(&mut self, v: ::std::string::String) {
+        self.duplicate_of = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_duplicate_of(&mut self) -> &mut ::std::string::String {
+        &mut self.duplicate_of
+    }
+
+    // Take field
+    pub fn take_duplicate_of(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.duplicate_of, ::std::string::String::new())
+    }
 }
 
 impl ::protobuf::Message for ProtoFile {
@@ -295,6 +397,33 @@ impl ::protobuf::Message for ProtoFile {
                     let tmp = is.read_float()?;
                     self.us_rating = tmp;
                 },
+                11 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.content_hash)?;
+                },
+                12 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.max_age = tmp;
+                },
+                13 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.version = tmp;
+                },
+                14 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.simhash = tmp;
+                },
+                15 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.duplicate_of)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -337,6 +466,21 @@ impl ::protobuf::Message for ProtoFile {
         if self.us_rating != 0. {
             my_size += 5;
         }
+        if !self.content_hash.is_empty() {
+            my_size += ::protobuf::rt::string_size(11, &self.content_hash);
+        }
+        if self.max_age != 0 {
+            my_size += ::protobuf::rt::value_size(12, self.max_age, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.version != 0 {
+            my_size += ::protobuf::rt::value_size(13, self.version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.simhash != 0 {
+            my_size += ::protobuf::rt::value_size(14, self.simhash, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.duplicate_of.is_empty() {
+            my_size += ::protobuf::rt::string_size(15, &self.duplicate_of);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -373,6 +517,21 @@ impl ::protobuf::Message for ProtoFile {
         if self.us_rating != 0. {
             os.write_float(10, self.us_rating)?;
         }
+        if !self.content_hash.is_empty() {
+            os.write_string(11, &self.content_hash)?;
+        }
+        if self.max_age != 0 {
+            os.write_int64(12, self.max_age)?;
+        }
+        if self.version != 0 {
+            os.write_uint32(13, self.version)?;
+        }
+        if self.simhash != 0 {
+            os.write_uint64(14, self.simhash)?;
+        }
+        if !self.duplicate_of.is_empty() {
+            os.write_string(15, &self.duplicate_of)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -461,6 +620,31 @@ impl ::protobuf::Message for ProtoFile {
                 |m: &ProtoFile| { &m.us_rating },
                 |m: &mut ProtoFile| { &mut m.us_rating },
             ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "content_hash",
+                |m: &ProtoFile| { &m.content_hash },
+                |m: &mut ProtoFile| { &mut m.content_hash },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "max_age",
+                |m: &ProtoFile| { &m.max_age },
+                |m: &mut ProtoFile| { &mut m.max_age },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "version",
+                |m: &ProtoFile| { &m.version },
+                |m: &mut ProtoFile| { &mut m.version },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "simhash",
+                |m: &ProtoFile| { &m.simhash },
+                |m: &mut ProtoFile| { &mut m.simhash },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "duplicate_of",
+                |m: &ProtoFile| { &m.duplicate_of },
+                |m: &mut ProtoFile| { &mut m.duplicate_of },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<ProtoFile>(
                 "ProtoFile",
                 fields,
@@ -487,6 +671,11 @@ impl ::protobuf::Clear for ProtoFile {
         self.gb_rating = 0.;
         self.ru_rating = 0.;
         self.us_rating = 0.;
+        self.content_hash.clear();
+        self.max_age = 0;
+        self.version = 0;
+        self.simhash = 0;
+        self.duplicate_of.clear();
         self.unknown_fields.clear();
     }
 }
@@ -503,6 +692,2783 @@ impl ::protobuf::reflect::ProtobufValue for ProtoFile {
     }
 }
 
+#[derive(PartialEq,Clone,Default)]
+pub struct Thread {
+    // message fields
+    pub title: ::std::string::String,
+    pub category: Category,
+    pub decay: i64,
+    pub articles: ::protobuf::RepeatedField<::std::string::String>,
+    pub times: ::std::vec::Vec<i64>,
+    pub centroid: ::std::vec::Vec<f32>,
+    pub id: ::std::string::String,
+    pub history: ::protobuf::RepeatedField<ThreadChange>,
+    pub hosts: ::protobuf::RepeatedField<::std::string::String>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Thread {
+    fn default() -> &'a Thread {
+        <Thread as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Thread {
+    pub fn new() -> Thread {
+        ::std::default::Default::default()
+    }
+
+    // string title = 1;
+
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+    pub fn clear_title(&mut self) {
+        self.title.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_title(&mut self, v: ::std::string::String) {
+        self.title = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_title(&mut self) -> &mut ::std::string::String {
+        &mut self.title
+    }
+
+    // Take field
+    pub fn take_title(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.title, ::std::string::String::new())
+    }
+
+    // .ServerFiles.Category category = 2;
+
+
+    pub fn get_category(&self) -> Category {
+        self.category
+    }
+    pub fn clear_category(&mut self) {
+        self.category = Category::Society;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_category(&mut self, v: Category) {
+        self.category = v;
+    }
+
+    // int64 decay = 3;
+
+
+    pub fn get_decay(&self) -> i64 {
+        self.decay
+    }
+    pub fn clear_decay(&mut self) {
+        self.decay = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_decay(&mut self, v: i64) {
+        self.decay = v;
+    }
+
+    // repeated string articles = 4;
+
+
+    pub fn get_articles(&self) -> &[::std::string::String] {
+        &self.articles
+    }
+    pub fn clear_articles(&mut self) {
+        self.articles.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_articles(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.articles = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_articles(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.articles
+    }
+
+    // Take field
+    pub fn take_articles(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.articles, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated int64 times = 5;
+
+
+    pub fn get_times(&self) -> &[i64] {
+        &self.times
+    }
+    pub fn clear_times(&mut self) {
+        self.times.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_times(&mut self, v: ::std::vec::Vec<i64>) {
+        self.times = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_times(&mut self) -> &mut ::std::vec::Vec<i64> {
+        &mut self.times
+    }
+
+    // Take field
+    pub fn take_times(&mut self) -> ::std::vec::Vec<i64> {
+        ::std::mem::replace(&mut self.times, ::std::vec::Vec::new())
+    }
+
+    // repeated float centroid = 6;
+
+
+    pub fn get_centroid(&self) -> &[f32] {
+        &self.centroid
+    }
+    pub fn clear_centroid(&mut self) {
+        self.centroid.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_centroid(&mut self, v: ::std::vec::Vec<f32>) {
+        self.centroid = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_centroid(&mut self) -> &mut ::std::vec::Vec<f32> {
+        &mut self.centroid
+    }
+
+    // Take field
+    pub fn take_centroid(&mut self) -> ::std::vec::Vec<f32> {
+        ::std::mem::replace(&mut self.centroid, ::std::vec::Vec::new())
+    }
+
+    // string id = 7;
+
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+    pub fn clear_id(&mut self) {
+        self.id.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: ::std::string::String) {
+        self.id = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_id(&mut self) -> &mut ::std::string::String {
+        &mut self.id
+    }
+
+    // Take field
+    pub fn take_id(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.id, ::std::string::String::new())
+    }
+
+    // repeated .ServerFiles.ThreadChange history = 8;
+
+
+    pub fn get_history(&self) -> &[ThreadChange] {
+        &self.history
+    }
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_history(&mut self, v: ::protobuf::RepeatedField<ThreadChange>) {
+        self.history = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_history(&mut self) -> &mut ::protobuf::RepeatedField<ThreadChange> {
+        &mut self.history
+    }
+
+    // Take field
+    pub fn take_history(&mut self) -> ::protobuf::RepeatedField<ThreadChange> {
+        ::std::mem::replace(&mut self.history, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated string hosts = 9;
+
+
+    pub fn get_hosts(&self) -> &[::std::string::String] {
+        &self.hosts
+    }
+    pub fn clear_hosts(&mut self) {
+        self.hosts.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_hosts(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.hosts = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_hosts(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.hosts
+    }
+
+    // Take field
+    pub fn take_hosts(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.hosts, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for Thread {
+    fn is_initialized(&self) -> bool {
+        for v in &self.history {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.title)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.category, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.decay = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.articles)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_int64_into(wire_type, is, &mut self.times)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_repeated_float_into(wire_type, is, &mut self.centroid)?;
+                },
+                7 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.id)?;
+                },
+                8 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.history)?;
+                },
+                9 => {
+                    ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.hosts)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.title.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.title);
+        }
+        if self.category != Category::Society {
+            my_size += ::protobuf::rt::enum_size(2, self.category);
+        }
+        if self.decay != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.decay, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.articles {
+            my_size += ::protobuf::rt::string_size(4, &value);
+        };
+        for value in &self.times {
+            my_size += ::protobuf::rt::value_size(5, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += 5 * self.centroid.len() as u32;
+        if !self.id.is_empty() {
+            my_size += ::protobuf::rt::string_size(7, &self.id);
+        }
+        for value in &self.history {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.hosts {
+            my_size += ::protobuf::rt::string_size(9, &value);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.title.is_empty() {
+            os.write_string(1, &self.title)?;
+        }
+        if self.category != Category::Society {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(&self.category))?;
+        }
+        if self.decay != 0 {
+            os.write_int64(3, self.decay)?;
+        }
+        for v in &self.articles {
+            os.write_string(4, &v)?;
+        };
+        for v in &self.times {
+            os.write_int64(5, *v)?;
+        };
+        for v in &self.centroid {
+            os.write_float(6, *v)?;
+        };
+        if !self.id.is_empty() {
+            os.write_string(7, &self.id)?;
+        }
+        for v in &self.history {
+            os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.hosts {
+            os.write_string(9, &v)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Thread {
+        Thread::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "title",
+                |m: &Thread| { &m.title },
+                |m: &mut Thread| { &mut m.title },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Category>>(
+                "category",
+                |m: &Thread| { &m.category },
+                |m: &mut Thread| { &mut m.category },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "decay",
+                |m: &Thread| { &m.decay },
+                |m: &mut Thread| { &mut m.decay },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "articles",
+                |m: &Thread| { &m.articles },
+                |m: &mut Thread| { &mut m.articles },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "times",
+                |m: &Thread| { &m.times },
+                |m: &mut Thread| { &mut m.times },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeFloat>(
+                "centroid",
+                |m: &Thread| { &m.centroid },
+                |m: &mut Thread| { &mut m.centroid },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "id",
+                |m: &Thread| { &m.id },
+                |m: &mut Thread| { &mut m.id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ThreadChange>>(
+                "history",
+                |m: &Thread| { &m.history },
+                |m: &mut Thread| { &mut m.history },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "hosts",
+                |m: &Thread| { &m.hosts },
+                |m: &mut Thread| { &mut m.hosts },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Thread>(
+                "Thread",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Thread {
+        static instance: ::protobuf::rt::LazyV2<Thread> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Thread::new)
+    }
+}
+
+impl ::protobuf::Clear for Thread {
+    fn clear(&mut self) {
+        self.title.clear();
+        self.category = Category::Society;
+        self.decay = 0;
+        self.articles.clear();
+        self.times.clear();
+        self.centroid.clear();
+        self.id.clear();
+        self.history.clear();
+        self.hosts.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Thread {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Thread {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ThreadChange {
+    // message fields
+    pub time: u64,
+    pub kind: ChangeKind,
+    pub title: ::std::string::String,
+    pub articles: u32,
+    pub added: u32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ThreadChange {
+    fn default() -> &'a ThreadChange {
+        <ThreadChange as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ThreadChange {
+    pub fn new() -> ThreadChange {
+        ::std::default::Default::default()
+    }
+
+    // uint64 time = 1;
+
+
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+    pub fn clear_time(&mut self) {
+        self.time = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_time(&mut self, v: u64) {
+        self.time = v;
+    }
+
+    // .ServerFiles.ChangeKind kind = 2;
+
+
+    pub fn get_kind(&self) -> ChangeKind {
+        self.kind
+    }
+    pub fn clear_kind(&mut self) {
+        self.kind = ChangeKind::Created;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_kind(&mut self, v: ChangeKind) {
+        self.kind = v;
+    }
+
+    // string title = 3;
+
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+    pub fn clear_title(&mut self) {
+        self.title.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_title(&mut self, v: ::std::string::String) {
+        self.title = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_title(&mut self) -> &mut ::std::string::String {
+        &mut self.title
+    }
+
+    // Take field
+    pub fn take_title(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.title, ::std::string::String::new())
+    }
+
+    // uint32 articles = 4;
+
+
+    pub fn get_articles(&self) -> u32 {
+        self.articles
+    }
+    pub fn clear_articles(&mut self) {
+        self.articles = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_articles(&mut self, v: u32) {
+        self.articles = v;
+    }
+
+    // uint32 added = 5;
+
+
+    pub fn get_added(&self) -> u32 {
+        self.added
+    }
+    pub fn clear_added(&mut self) {
+        self.added = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_added(&mut self, v: u32) {
+        self.added = v;
+    }
+}
+
+impl ::protobuf::Message for ThreadChange {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.time = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.kind, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.title)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.articles = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.added = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.time != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.time, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.kind != ChangeKind::Created {
+            my_size += ::protobuf::rt::enum_size(2, self.kind);
+        }
+        if !self.title.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.title);
+        }
+        if self.articles != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.articles, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.added != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.added, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.time != 0 {
+            os.write_uint64(1, self.time)?;
+        }
+        if self.kind != ChangeKind::Created {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(&self.kind))?;
+        }
+        if !self.title.is_empty() {
+            os.write_string(3, &self.title)?;
+        }
+        if self.articles != 0 {
+            os.write_uint32(4, self.articles)?;
+        }
+        if self.added != 0 {
+            os.write_uint32(5, self.added)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ThreadChange {
+        ThreadChange::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "time",
+                |m: &ThreadChange| { &m.time },
+                |m: &mut ThreadChange| { &mut m.time },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<ChangeKind>>(
+                "kind",
+                |m: &ThreadChange| { &m.kind },
+                |m: &mut ThreadChange| { &mut m.kind },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "title",
+                |m: &ThreadChange| { &m.title },
+                |m: &mut ThreadChange| { &mut m.title },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "articles",
+                |m: &ThreadChange| { &m.articles },
+                |m: &mut ThreadChange| { &mut m.articles },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "added",
+                |m: &ThreadChange| { &m.added },
+                |m: &mut ThreadChange| { &mut m.added },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ThreadChange>(
+                "ThreadChange",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ThreadChange {
+        static instance: ::protobuf::rt::LazyV2<ThreadChange> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ThreadChange::new)
+    }
+}
+
+impl ::protobuf::Clear for ThreadChange {
+    fn clear(&mut self) {
+        self.time = 0;
+        self.kind = ChangeKind::Created;
+        self.title.clear();
+        self.articles = 0;
+        self.added = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ThreadChange {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ThreadChange {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Timeline {
+    // message fields
+    pub id: ::std::string::String,
+    pub first_seen: u64,
+    pub last_updated: u64,
+    pub expired: u64,
+    pub peak_size: u32,
+    pub arrivals: ::protobuf::RepeatedField<Arrival>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Timeline {
+    fn default() -> &'a Timeline {
+        <Timeline as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Timeline {
+    pub fn new() -> Timeline {
+        ::std::default::Default::default()
+    }
+
+    // string id = 1;
+
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+    pub fn clear_id(&mut self) {
+        self.id.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: ::std::string::String) {
+        self.id = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_id(&mut self) -> &mut ::std::string::String {
+        &mut self.id
+    }
+
+    // Take field
+    pub fn take_id(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.id, ::std::string::String::new())
+    }
+
+    // uint64 first_seen = 2;
+
+
+    pub fn get_first_seen(&self) -> u64 {
+        self.first_seen
+    }
+    pub fn clear_first_seen(&mut self) {
+        self.first_seen = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_first_seen(&mut self, v: u64) {
+        self.first_seen = v;
+    }
+
+    // uint64 last_updated = 3;
+
+
+    pub fn get_last_updated(&self) -> u64 {
+        self.last_updated
+    }
+    pub fn clear_last_updated(&mut self) {
+        self.last_updated = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_updated(&mut self, v: u64) {
+        self.last_updated = v;
+    }
+
+    // uint64 expired = 4;
+
+
+    pub fn get_expired(&self) -> u64 {
+        self.expired
+    }
+    pub fn clear_expired(&mut self) {
+        self.expired = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_expired(&mut self, v: u64) {
+        self.expired = v;
+    }
+
+    // uint32 peak_size = 5;
+
+
+    pub fn get_peak_size(&self) -> u32 {
+        self.peak_size
+    }
+    pub fn clear_peak_size(&mut self) {
+        self.peak_size = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_peak_size(&mut self, v: u32) {
+        self.peak_size = v;
+    }
+
+    // repeated .ServerFiles.Arrival arrivals = 6;
+
+
+    pub fn get_arrivals(&self) -> &[Arrival] {
+        &self.arrivals
+    }
+    pub fn clear_arrivals(&mut self) {
+        self.arrivals.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_arrivals(&mut self, v: ::protobuf::RepeatedField<Arrival>) {
+        self.arrivals = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_arrivals(&mut self) -> &mut ::protobuf::RepeatedField<Arrival> {
+        &mut self.arrivals
+    }
+
+    // Take field
+    pub fn take_arrivals(&mut self) -> ::protobuf::RepeatedField<Arrival> {
+        ::std::mem::replace(&mut self.arrivals, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for Timeline {
+    fn is_initialized(&self) -> bool {
+        for v in &self.arrivals {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.id)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.first_seen = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.last_updated = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.expired = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.peak_size = tmp;
+                },
+                6 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.arrivals)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.id.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.id);
+        }
+        if self.first_seen != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.first_seen, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.last_updated != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.last_updated, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.expired != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.expired, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.peak_size != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.peak_size, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.arrivals {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.id.is_empty() {
+            os.write_string(1, &self.id)?;
+        }
+        if self.first_seen != 0 {
+            os.write_uint64(2, self.first_seen)?;
+        }
+        if self.last_updated != 0 {
+            os.write_uint64(3, self.last_updated)?;
+        }
+        if self.expired != 0 {
+            os.write_uint64(4, self.expired)?;
+        }
+        if self.peak_size != 0 {
+            os.write_uint32(5, self.peak_size)?;
+        }
+        for v in &self.arrivals {
+            os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Timeline {
+        Timeline::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "id",
+                |m: &Timeline| { &m.id },
+                |m: &mut Timeline| { &mut m.id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "first_seen",
+                |m: &Timeline| { &m.first_seen },
+                |m: &mut Timeline| { &mut m.first_seen },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "last_updated",
+                |m: &Timeline| { &m.last_updated },
+                |m: &mut Timeline| { &mut m.last_updated },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "expired",
+                |m: &Timeline| { &m.expired },
+                |m: &mut Timeline| { &mut m.expired },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "peak_size",
+                |m: &Timeline| { &m.peak_size },
+                |m: &mut Timeline| { &mut m.peak_size },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Arrival>>(
+                "arrivals",
+                |m: &Timeline| { &m.arrivals },
+                |m: &mut Timeline| { &mut m.arrivals },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Timeline>(
+                "Timeline",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Timeline {
+        static instance: ::protobuf::rt::LazyV2<Timeline> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Timeline::new)
+    }
+}
+
+impl ::protobuf::Clear for Timeline {
+    fn clear(&mut self) {
+        self.id.clear();
+        self.first_seen = 0;
+        self.last_updated = 0;
+        self.expired = 0;
+        self.peak_size = 0;
+        self.arrivals.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Timeline {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Timeline {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Arrival {
+    // message fields
+    pub article: ::std::string::String,
+    pub joined: u64,
+    pub published: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Arrival {
+    fn default() -> &'a Arrival {
+        <Arrival as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Arrival {
+    pub fn new() -> Arrival {
+        ::std::default::Default::default()
+    }
+
+    // string article = 1;
+
+
+    pub fn get_article(&self) -> &str {
+        &self.article
+    }
+    pub fn clear_article(&mut self) {
+        self.article.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_article(&mut self, v: ::std::string::String) {
+        self.article = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_article(&mut self) -> &mut ::std::string::String {
+        &mut self.article
+    }
+
+    // Take field
+    pub fn take_article(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.article, ::std::string::String::new())
+    }
+
+    // uint64 joined = 2;
+
+
+    pub fn get_joined(&self) -> u64 {
+        self.joined
+    }
+    pub fn clear_joined(&mut self) {
+        self.joined = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_joined(&mut self, v: u64) {
+        self.joined = v;
+    }
+
+    // int64 published = 3;
+
+
+    pub fn get_published(&self) -> i64 {
+        self.published
+    }
+    pub fn clear_published(&mut self) {
+        self.published = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_published(&mut self, v: i64) {
+        self.published = v;
+    }
+}
+
+impl ::protobuf::Message for Arrival {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.article)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.joined = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.published = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.article.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.article);
+        }
+        if self.joined != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.joined, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.published != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.published, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.article.is_empty() {
+            os.write_string(1, &self.article)?;
+        }
+        if self.joined != 0 {
+            os.write_uint64(2, self.joined)?;
+        }
+        if self.published != 0 {
+            os.write_int64(3, self.published)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Arrival {
+        Arrival::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "article",
+                |m: &Arrival| { &m.article },
+                |m: &mut Arrival| { &mut m.article },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "joined",
+                |m: &Arrival| { &m.joined },
+                |m: &mut Arrival| { &mut m.joined },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "published",
+                |m: &Arrival| { &m.published },
+                |m: &mut Arrival| { &mut m.published },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Arrival>(
+                "Arrival",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Arrival {
+        static instance: ::protobuf::rt::LazyV2<Arrival> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Arrival::new)
+    }
+}
+
+impl ::protobuf::Clear for Arrival {
+    fn clear(&mut self) {
+        self.article.clear();
+        self.joined = 0;
+        self.published = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Arrival {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Arrival {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SourceStats {
+    // message fields
+    pub host: ::std::string::String,
+    pub articles: u64,
+    pub not_news: u64,
+    pub threads: u64,
+    pub first_reports: u64,
+    pub large_threads: u64,
+    pub live: ::protobuf::RepeatedField<ThreadShare>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SourceStats {
+    fn default() -> &'a SourceStats {
+        <SourceStats as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SourceStats {
+    pub fn new() -> SourceStats {
+        ::std::default::Default::default()
+    }
+
+    // string host = 1;
+
+
+    pub fn get_host(&self) -> &str {
+        &self.host
+    }
+    pub fn clear_host(&mut self) {
+        self.host.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_host(&mut self, v: ::std::string::String) {
+        self.host = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_host(&mut self) -> &mut ::std::string::String {
+        &mut self.host
+    }
+
+    // Take field
+    pub fn take_host(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.host, ::std::string::String::new())
+    }
+
+    // uint64 articles = 2;
+
+
+    pub fn get_articles(&self) -> u64 {
+        self.articles
+    }
+    pub fn clear_articles(&mut self) {
+        self.articles = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_articles(&mut self, v: u64) {
+        self.articles = v;
+    }
+
+    // uint64 not_news = 3;
+
+
+    pub fn get_not_news(&self) -> u64 {
+        self.not_news
+    }
+    pub fn clear_not_news(&mut self) {
+        self.not_news = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_not_news(&mut self, v: u64) {
+        self.not_news = v;
+    }
+
+    // uint64 threads = 4;
+
+
+    pub fn get_threads(&self) -> u64 {
+        self.threads
+    }
+    pub fn clear_threads(&mut self) {
+        self.threads = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_threads(&mut self, v: u64) {
+        self.threads = v;
+    }
+
+    // uint64 first_reports = 5;
+
+
+    pub fn get_first_reports(&self) -> u64 {
+        self.first_reports
+    }
+    pub fn clear_first_reports(&mut self) {
+        self.first_reports = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_first_reports(&mut self, v: u64) {
+        self.first_reports = v;
+    }
+
+    // uint64 large_threads = 6;
+
+
+    pub fn get_large_threads(&self) -> u64 {
+        self.large_threads
+    }
+    pub fn clear_large_threads(&mut self) {
+        self.large_threads = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_large_threads(&mut self, v: u64) {
+        self.large_threads = v;
+    }
+
+    // repeated .ServerFiles.ThreadShare live = 7;
+
+
+    pub fn get_live(&self) -> &[ThreadShare] {
+        &self.live
+    }
+    pub fn clear_live(&mut self) {
+        self.live.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_live(&mut self, v: ::protobuf::RepeatedField<ThreadShare>) {
+        self.live = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_live(&mut self) -> &mut ::protobuf::RepeatedField<ThreadShare> {
+        &mut self.live
+    }
+
+    // Take field
+    pub fn take_live(&mut self) -> ::protobuf::RepeatedField<ThreadShare> {
+        ::std::mem::replace(&mut self.live, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for SourceStats {
+    fn is_initialized(&self) -> bool {
+        for v in &self.live {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.host)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.articles = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.not_news = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.threads = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.first_reports = tmp;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.large_threads = tmp;
+                },
+                7 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.live)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.host.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.host);
+        }
+        if self.articles != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.articles, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.not_news != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.not_news, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.threads != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.threads, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.first_reports != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.first_reports, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.large_threads != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.large_threads, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.live {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.host.is_empty() {
+            os.write_string(1, &self.host)?;
+        }
+        if self.articles != 0 {
+            os.write_uint64(2, self.articles)?;
+        }
+        if self.not_news != 0 {
+            os.write_uint64(3, self.not_news)?;
+        }
+        if self.threads != 0 {
+            os.write_uint64(4, self.threads)?;
+        }
+        if self.first_reports != 0 {
+            os.write_uint64(5, self.first_reports)?;
+        }
+        if self.large_threads != 0 {
+            os.write_uint64(6, self.large_threads)?;
+        }
+        for v in &self.live {
+            os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SourceStats {
+        SourceStats::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "host",
+                |m: &SourceStats| { &m.host },
+                |m: &mut SourceStats| { &mut m.host },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "articles",
+                |m: &SourceStats| { &m.articles },
+                |m: &mut SourceStats| { &mut m.articles },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "not_news",
+                |m: &SourceStats| { &m.not_news },
+                |m: &mut SourceStats| { &mut m.not_news },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "threads",
+                |m: &SourceStats| { &m.threads },
+                |m: &mut SourceStats| { &mut m.threads },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "first_reports",
+                |m: &SourceStats| { &m.first_reports },
+                |m: &mut SourceStats| { &mut m.first_reports },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "large_threads",
+                |m: &SourceStats| { &m.large_threads },
+                |m: &mut SourceStats| { &mut m.large_threads },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ThreadShare>>(
+                "live",
+                |m: &SourceStats| { &m.live },
+                |m: &mut SourceStats| { &mut m.live },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SourceStats>(
+                "SourceStats",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SourceStats {
+        static instance: ::protobuf::rt::LazyV2<SourceStats> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SourceStats::new)
+    }
+}
+
+impl ::protobuf::Clear for SourceStats {
+    fn clear(&mut self) {
+        self.host.clear();
+        self.articles = 0;
+        self.not_news = 0;
+        self.threads = 0;
+        self.first_reports = 0;
+        self.large_threads = 0;
+        self.live.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SourceStats {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SourceStats {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ThreadShare {
+    // message fields
+    pub thread: ::std::string::String,
+    pub first: bool,
+    pub large: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ThreadShare {
+    fn default() -> &'a ThreadShare {
+        <ThreadShare as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ThreadShare {
+    pub fn new() -> ThreadShare {
+        ::std::default::Default::default()
+    }
+
+    // string thread = 1;
+
+
+    pub fn get_thread(&self) -> &str {
+        &self.thread
+    }
+    pub fn clear_thread(&mut self) {
+        self.thread.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_thread(&mut self, v: ::std::string::String) {
+        self.thread = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_thread(&mut self) -> &mut ::std::string::String {
+        &mut self.thread
+    }
+
+    // Take field
+    pub fn take_thread(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.thread, ::std::string::String::new())
+    }
+
+    // bool first = 2;
+
+
+    pub fn get_first(&self) -> bool {
+        self.first
+    }
+    pub fn clear_first(&mut self) {
+        self.first = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_first(&mut self, v: bool) {
+        self.first = v;
+    }
+
+    // bool large = 3;
+
+
+    pub fn get_large(&self) -> bool {
+        self.large
+    }
+    pub fn clear_large(&mut self) {
+        self.large = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_large(&mut self, v: bool) {
+        self.large = v;
+    }
+}
+
+impl ::protobuf::Message for ThreadShare {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.thread)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.first = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.large = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.thread.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.thread);
+        }
+        if self.first != false {
+            my_size += 2;
+        }
+        if self.large != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.thread.is_empty() {
+            os.write_string(1, &self.thread)?;
+        }
+        if self.first != false {
+            os.write_bool(2, self.first)?;
+        }
+        if self.large != false {
+            os.write_bool(3, self.large)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ThreadShare {
+        ThreadShare::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "thread",
+                |m: &ThreadShare| { &m.thread },
+                |m: &mut ThreadShare| { &mut m.thread },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "first",
+                |m: &ThreadShare| { &m.first },
+                |m: &mut ThreadShare| { &mut m.first },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "large",
+                |m: &ThreadShare| { &m.large },
+                |m: &mut ThreadShare| { &mut m.large },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ThreadShare>(
+                "ThreadShare",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ThreadShare {
+        static instance: ::protobuf::rt::LazyV2<ThreadShare> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ThreadShare::new)
+    }
+}
+
+impl ::protobuf::Clear for ThreadShare {
+    fn clear(&mut self) {
+        self.thread.clear();
+        self.first = false;
+        self.large = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ThreadShare {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ThreadShare {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ClusterSnapshot {
+    // message fields
+    pub generation: u64,
+    pub language: Language,
+    pub all: ::protobuf::RepeatedField<Thread>,
+    pub categories: ::protobuf::RepeatedField<Thread>,
+    pub articles: u64,
+    pub representation: ::std::string::String,
+    pub window: u64,
+    pub doubling: u64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ClusterSnapshot {
+    fn default() -> &'a ClusterSnapshot {
+        <ClusterSnapshot as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ClusterSnapshot {
+    pub fn new() -> ClusterSnapshot {
+        ::std::default::Default::default()
+    }
+
+    // uint64 generation = 1;
+
+
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn clear_generation(&mut self) {
+        self.generation = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_generation(&mut self, v: u64) {
+        self.generation = v;
+    }
+
+    // .ServerFiles.Language language = 2;
+
+
+    pub fn get_language(&self) -> Language {
+        self.language
+    }
+    pub fn clear_language(&mut self) {
+        self.language = Language::Eng;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_language(&mut self, v: Language) {
+        self.language = v;
+    }
+
+    // repeated .ServerFiles.Thread all = 3;
+
+
+    pub fn get_all(&self) -> &[Thread] {
+        &self.all
+    }
+    pub fn clear_all(&mut self) {
+        self.all.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_all(&mut self, v: ::protobuf::RepeatedField<Thread>) {
+        self.all = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_all(&mut self) -> &mut ::protobuf::RepeatedField<Thread> {
+        &mut self.all
+    }
+
+    // Take field
+    pub fn take_all(&mut self) -> ::protobuf::RepeatedField<Thread> {
+        ::std::mem::replace(&mut self.all, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .ServerFiles.Thread categories = 4;
+
+
+    pub fn get_categories(&self) -> &[Thread] {
+        &self.categories
+    }
+    pub fn clear_categories(&mut self) {
+        self.categories.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_categories(&mut self, v: ::protobuf::RepeatedField<Thread>) {
+        self.categories = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_categories(&mut self) -> &mut ::protobuf::RepeatedField<Thread> {
+        &mut self.categories
+    }
+
+    // Take field
+    pub fn take_categories(&mut self) -> ::protobuf::RepeatedField<Thread> {
+        ::std::mem::replace(&mut self.categories, ::protobuf::RepeatedField::new())
+    }
+
+    // uint64 articles = 5;
+
+
+    pub fn get_articles(&self) -> u64 {
+        self.articles
+    }
+    pub fn clear_articles(&mut self) {
+        self.articles = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_articles(&mut self, v: u64) {
+        self.articles = v;
+    }
+
+    // string representation = 6;
+
+
+    pub fn get_representation(&self) -> &str {
+        &self.representation
+    }
+    pub fn clear_representation(&mut self) {
+        self.representation.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_representation(&mut self, v: ::std::string::String) {
+        self.representation = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_representation(&mut self) -> &mut ::std::string::String {
+        &mut self.representation
+    }
+
+    // Take field
+    pub fn take_representation(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.representation, ::std::string::String::new())
+    }
+
+    // uint64 window = 7;
+
+
+    pub fn get_window(&self) -> u64 {
+        self.window
+    }
+    pub fn clear_window(&mut self) {
+        self.window = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_window(&mut self, v: u64) {
+        self.window = v;
+    }
+
+    // uint64 doubling = 8;
+
+
+    pub fn get_doubling(&self) -> u64 {
+        self.doubling
+    }
+    pub fn clear_doubling(&mut self) {
+        self.doubling = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_doubling(&mut self, v: u64) {
+        self.doubling = v;
+    }
+}
+
+impl ::protobuf::Message for ClusterSnapshot {
+    fn is_initialized(&self) -> bool {
+        for v in &self.all {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.categories {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.generation = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.language, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.all)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.categories)?;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.articles = tmp;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.representation)?;
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.window = tmp;
+                },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.doubling = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.generation != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.generation, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.language != Language::Eng {
+            my_size += ::protobuf::rt::enum_size(2, self.language);
+        }
+        for value in &self.all {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.categories {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.articles != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.articles, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.representation.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.representation);
+        }
+        if self.window != 0 {
+            my_size += ::protobuf::rt::value_size(7, self.window, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.doubling != 0 {
+            my_size += ::protobuf::rt::value_size(8, self.doubling, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.generation != 0 {
+            os.write_uint64(1, self.generation)?;
+        }
+        if self.language != Language::Eng {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(&self.language))?;
+        }
+        for v in &self.all {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.categories {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.articles != 0 {
+            os.write_uint64(5, self.articles)?;
+        }
+        if !self.representation.is_empty() {
+            os.write_string(6, &self.representation)?;
+        }
+        if self.window != 0 {
+            os.write_uint64(7, self.window)?;
+        }
+        if self.doubling != 0 {
+            os.write_uint64(8, self.doubling)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ClusterSnapshot {
+        ClusterSnapshot::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "generation",
+                |m: &ClusterSnapshot| { &m.generation },
+                |m: &mut ClusterSnapshot| { &mut m.generation },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Language>>(
+                "language",
+                |m: &ClusterSnapshot| { &m.language },
+                |m: &mut ClusterSnapshot| { &mut m.language },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Thread>>(
+                "all",
+                |m: &ClusterSnapshot| { &m.all },
+                |m: &mut ClusterSnapshot| { &mut m.all },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Thread>>(
+                "categories",
+                |m: &ClusterSnapshot| { &m.categories },
+                |m: &mut ClusterSnapshot| { &mut m.categories },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "articles",
+                |m: &ClusterSnapshot| { &m.articles },
+                |m: &mut ClusterSnapshot| { &mut m.articles },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "representation",
+                |m: &ClusterSnapshot| { &m.representation },
+                |m: &mut ClusterSnapshot| { &mut m.representation },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "window",
+                |m: &ClusterSnapshot| { &m.window },
+                |m: &mut ClusterSnapshot| { &mut m.window },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "doubling",
+                |m: &ClusterSnapshot| { &m.doubling },
+                |m: &mut ClusterSnapshot| { &mut m.doubling },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ClusterSnapshot>(
+                "ClusterSnapshot",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ClusterSnapshot {
+        static instance: ::protobuf::rt::LazyV2<ClusterSnapshot> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ClusterSnapshot::new)
+    }
+}
+
+impl ::protobuf::Clear for ClusterSnapshot {
+    fn clear(&mut self) {
+        self.generation = 0;
+        self.language = Language::Eng;
+        self.all.clear();
+        self.categories.clear();
+        self.articles = 0;
+        self.representation.clear();
+        self.window = 0;
+        self.doubling = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ClusterSnapshot {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ClusterSnapshot {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ExportHeader {
+    // message fields
+    pub version: u32,
+    pub ttl: u64,
+    pub threads: ::protobuf::RepeatedField<ClusterSnapshot>,
+    pub timelines: ::protobuf::RepeatedField<Timeline>,
+    pub sources: ::protobuf::RepeatedField<SourceStats>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ExportHeader {
+    fn default() -> &'a ExportHeader {
+        <ExportHeader as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ExportHeader {
+    pub fn new() -> ExportHeader {
+        ::std::default::Default::default()
+    }
+
+    // uint32 version = 1;
+
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+    pub fn clear_version(&mut self) {
+        self.version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_version(&mut self, v: u32) {
+        self.version = v;
+    }
+
+    // uint64 ttl = 2;
+
+
+    pub fn get_ttl(&self) -> u64 {
+        self.ttl
+    }
+    pub fn clear_ttl(&mut self) {
+        self.ttl = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ttl(&mut self, v: u64) {
+        self.ttl = v;
+    }
+
+    // repeated .ServerFiles.ClusterSnapshot threads = 3;
+
+
+    pub fn get_threads(&self) -> &[ClusterSnapshot] {
+        &self.threads
+    }
+    pub fn clear_threads(&mut self) {
+        self.threads.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_threads(&mut self, v: ::protobuf::RepeatedField<ClusterSnapshot>) {
+        self.threads = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_threads(&mut self) -> &mut ::protobuf::RepeatedField<ClusterSnapshot> {
+        &mut self.threads
+    }
+
+    // Take field
+    pub fn take_threads(&mut self) -> ::protobuf::RepeatedField<ClusterSnapshot> {
+        ::std::mem::replace(&mut self.threads, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .ServerFiles.Timeline timelines = 4;
+
+
+    pub fn get_timelines(&self) -> &[Timeline] {
+        &self.timelines
+    }
+    pub fn clear_timelines(&mut self) {
+        self.timelines.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timelines(&mut self, v: ::protobuf::RepeatedField<Timeline>) {
+        self.timelines = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_timelines(&mut self) -> &mut ::protobuf::RepeatedField<Timeline> {
+        &mut self.timelines
+    }
+
+    // Take field
+    pub fn take_timelines(&mut self) -> ::protobuf::RepeatedField<Timeline> {
+        ::std::mem::replace(&mut self.timelines, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .ServerFiles.SourceStats sources = 5;
+
+
+    pub fn get_sources(&self) -> &[SourceStats] {
+        &self.sources
+    }
+    pub fn clear_sources(&mut self) {
+        self.sources.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sources(&mut self, v: ::protobuf::RepeatedField<SourceStats>) {
+        self.sources = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_sources(&mut self) -> &mut ::protobuf::RepeatedField<SourceStats> {
+        &mut self.sources
+    }
+
+    // Take field
+    pub fn take_sources(&mut self) -> ::protobuf::RepeatedField<SourceStats> {
+        ::std::mem::replace(&mut self.sources, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for ExportHeader {
+    fn is_initialized(&self) -> bool {
+        for v in &self.threads {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.timelines {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.sources {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.version = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.ttl = tmp;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.threads)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.timelines)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.sources)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.version != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.ttl != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.ttl, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.threads {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.timelines {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.sources {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.version != 0 {
+            os.write_uint32(1, self.version)?;
+        }
+        if self.ttl != 0 {
+            os.write_uint64(2, self.ttl)?;
+        }
+        for v in &self.threads {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.timelines {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.sources {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ExportHeader {
+        ExportHeader::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "version",
+                |m: &ExportHeader| { &m.version },
+                |m: &mut ExportHeader| { &mut m.version },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "ttl",
+                |m: &ExportHeader| { &m.ttl },
+                |m: &mut ExportHeader| { &mut m.ttl },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ClusterSnapshot>>(
+                "threads",
+                |m: &ExportHeader| { &m.threads },
+                |m: &mut ExportHeader| { &mut m.threads },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Timeline>>(
+                "timelines",
+                |m: &ExportHeader| { &m.timelines },
+                |m: &mut ExportHeader| { &mut m.timelines },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SourceStats>>(
+                "sources",
+                |m: &ExportHeader| { &m.sources },
+                |m: &mut ExportHeader| { &mut m.sources },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ExportHeader>(
+                "ExportHeader",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ExportHeader {
+        static instance: ::protobuf::rt::LazyV2<ExportHeader> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ExportHeader::new)
+    }
+}
+
+impl ::protobuf::Clear for ExportHeader {
+    fn clear(&mut self) {
+        self.version = 0;
+        self.ttl = 0;
+        self.threads.clear();
+        self.timelines.clear();
+        self.sources.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ExportHeader {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ExportHeader {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum ChangeKind {
+    Created = 0,
+    Grew = 1,
+    Retitled = 2,
+}
+
+impl ::protobuf::ProtobufEnum for ChangeKind {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<ChangeKind> {
+        match value {
+            0 => ::std::option::Option::Some(ChangeKind::Created),
+            1 => ::std::option::Option::Some(ChangeKind::Grew),
+            2 => ::std::option::Option::Some(ChangeKind::Retitled),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [ChangeKind] = &[
+            ChangeKind::Created,
+            ChangeKind::Grew,
+            ChangeKind::Retitled,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<ChangeKind>("ChangeKind", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for ChangeKind {
+}
+
+impl ::std::default::Default for ChangeKind {
+    fn default() -> Self {
+        ChangeKind::Created
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChangeKind {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
 #[derive(Clone,PartialEq,Eq,Debug,Hash)]
 pub enum Language {
     Eng = 0,
@@ -619,7 +3585,7 @@ impl ::protobuf::reflect::ProtobufValue for Category {
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x19protos/server_files.proto\x12\x0bServerFiles\"\xd0\x02\n\tProtoFil\
+    \n\x19protos/server_files.proto\x12\x0bServerFiles\"\xe3\x03\n\tProtoFil\
     e\x12\x14\n\x05title\x18\x01\x20\x01(\tR\x05title\x12\x1b\n\tfile_name\
     \x18\x02\x20\x01(\tR\x08fileName\x12\x10\n\x03url\x18\x03\x20\x01(\tR\
     \x03url\x12%\n\x0edate_published\x18\x04\x20\x01(\x03R\rdatePublished\
@@ -628,11 +3594,56 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     eR\x08language\x12\x1a\n\x08accuracy\x18\x07\x20\x01(\x02R\x08accuracy\
     \x12\x1b\n\tgb_rating\x18\x08\x20\x01(\x02R\x08gbRating\x12\x1b\n\tru_ra\
     ting\x18\t\x20\x01(\x02R\x08ruRating\x12\x1b\n\tus_rating\x18\n\x20\x01(\
-    \x02R\x08usRating*\x1c\n\x08Language\x12\x07\n\x03Eng\x10\0\x12\x07\n\
-    \x03Rus\x10\x01*k\n\x08Category\x12\x0b\n\x07Society\x10\0\x12\x0b\n\x07\
-    Economy\x10\x01\x12\x0e\n\nTechnology\x10\x02\x12\x11\n\rEntertainment\
-    \x10\x03\x12\n\n\x06Sports\x10\x04\x12\x0b\n\x07Science\x10\x05\x12\t\n\
-    \x05Other\x10\x06b\x06proto3\
+    \x02R\x08usRating\x12!\n\x0ccontent_hash\x18\x0b\x20\x01(\tR\x0bcontentH\
+    ash\x12\x17\n\x07max_age\x18\x0c\x20\x01(\x03R\x06maxAge\x12\x18\n\x07ve\
+    rsion\x18\r\x20\x01(\rR\x07version\x12\x18\n\x07simhash\x18\x0e\x20\x01(\
+    \x04R\x07simhash\x12!\n\x0cduplicate_of\x18\x0f\x20\x01(\tR\x0bduplicate\
+    Of\"\x90\x02\n\x06Thread\x12\x14\n\x05title\x18\x01\x20\x01(\tR\x05title\
+    \x121\n\x08category\x18\x02\x20\x01(\x0e2\x15.ServerFiles.CategoryR\x08c\
+    ategory\x12\x14\n\x05decay\x18\x03\x20\x01(\x03R\x05decay\x12\x1a\n\x08a\
+    rticles\x18\x04\x20\x03(\tR\x08articles\x12\x14\n\x05times\x18\x05\x20\
+    \x03(\x03R\x05times\x12\x1a\n\x08centroid\x18\x06\x20\x03(\x02R\x08centr\
+    oid\x12\x0e\n\x02id\x18\x07\x20\x01(\tR\x02id\x123\n\x07history\x18\x08\
+    \x20\x03(\x0b2\x19.ServerFiles.ThreadChangeR\x07history\x12\x14\n\x05hos\
+    ts\x18\t\x20\x03(\tR\x05hosts\"\x97\x01\n\x0cThreadChange\x12\x12\n\x04t\
+    ime\x18\x01\x20\x01(\x04R\x04time\x12+\n\x04kind\x18\x02\x20\x01(\x0e2\
+    \x17.ServerFiles.ChangeKindR\x04kind\x12\x14\n\x05title\x18\x03\x20\x01(\
+    \tR\x05title\x12\x1a\n\x08articles\x18\x04\x20\x01(\rR\x08articles\x12\
+    \x14\n\x05added\x18\x05\x20\x01(\rR\x05added\"\xc5\x01\n\x08Timeline\x12\
+    \x0e\n\x02id\x18\x01\x20\x01(\tR\x02id\x12\x1d\n\nfirst_seen\x18\x02\x20\
+    \x01(\x04R\tfirstSeen\x12!\n\x0clast_updated\x18\x03\x20\x01(\x04R\x0bla\
+    stUpdated\x12\x18\n\x07expired\x18\x04\x20\x01(\x04R\x07expired\x12\x1b\
+    \n\tpeak_size\x18\x05\x20\x01(\rR\x08peakSize\x120\n\x08arrivals\x18\x06\
+    \x20\x03(\x0b2\x14.ServerFiles.ArrivalR\x08arrivals\"Y\n\x07Arrival\x12\
+    \x18\n\x07article\x18\x01\x20\x01(\tR\x07article\x12\x16\n\x06joined\x18\
+    \x02\x20\x01(\x04R\x06joined\x12\x1c\n\tpublished\x18\x03\x20\x01(\x03R\
+    \tpublished\"\xea\x01\n\x0bSourceStats\x12\x12\n\x04host\x18\x01\x20\x01\
+    (\tR\x04host\x12\x1a\n\x08articles\x18\x02\x20\x01(\x04R\x08articles\x12\
+    \x19\n\x08not_news\x18\x03\x20\x01(\x04R\x07notNews\x12\x18\n\x07threads\
+    \x18\x04\x20\x01(\x04R\x07threads\x12#\n\rfirst_reports\x18\x05\x20\x01(\
+    \x04R\x0cfirstReports\x12#\n\rlarge_threads\x18\x06\x20\x01(\x04R\x0clar\
+    geThreads\x12,\n\x04live\x18\x07\x20\x03(\x0b2\x18.ServerFiles.ThreadSha\
+    reR\x04live\"Q\n\x0bThreadShare\x12\x16\n\x06thread\x18\x01\x20\x01(\tR\
+    \x06thread\x12\x14\n\x05first\x18\x02\x20\x01(\x08R\x05first\x12\x14\n\
+    \x05large\x18\x03\x20\x01(\x08R\x05large\"\xb8\x02\n\x0fClusterSnapshot\
+    \x12\x1e\n\ngeneration\x18\x01\x20\x01(\x04R\ngeneration\x121\n\x08langu\
+    age\x18\x02\x20\x01(\x0e2\x15.ServerFiles.LanguageR\x08language\x12%\n\
+    \x03all\x18\x03\x20\x03(\x0b2\x13.ServerFiles.ThreadR\x03all\x123\n\ncat\
+    egories\x18\x04\x20\x03(\x0b2\x13.ServerFiles.ThreadR\ncategories\x12\
+    \x1a\n\x08articles\x18\x05\x20\x01(\x04R\x08articles\x12&\n\x0erepresent\
+    ation\x18\x06\x20\x01(\tR\x0erepresentation\x12\x16\n\x06window\x18\x07\
+    \x20\x01(\x04R\x06window\x12\x1a\n\x08doubling\x18\x08\x20\x01(\x04R\x08\
+    doubling\"\xdb\x01\n\x0cExportHeader\x12\x18\n\x07version\x18\x01\x20\
+    \x01(\rR\x07version\x12\x10\n\x03ttl\x18\x02\x20\x01(\x04R\x03ttl\x126\n\
+    \x07threads\x18\x03\x20\x03(\x0b2\x1c.ServerFiles.ClusterSnapshotR\x07th\
+    reads\x123\n\ttimelines\x18\x04\x20\x03(\x0b2\x15.ServerFiles.TimelineR\
+    \ttimelines\x122\n\x07sources\x18\x05\x20\x03(\x0b2\x18.ServerFiles.Sour\
+    ceStatsR\x07sources*1\n\nChangeKind\x12\x0b\n\x07Created\x10\0\x12\x08\n\
+    \x04Grew\x10\x01\x12\x0c\n\x08Retitled\x10\x02*\x1c\n\x08Language\x12\
+    \x07\n\x03Eng\x10\0\x12\x07\n\x03Rus\x10\x01*k\n\x08Category\x12\x0b\n\
+    \x07Society\x10\0\x12\x0b\n\x07Economy\x10\x01\x12\x0e\n\nTechnology\x10\
+    \x02\x12\x11\n\rEntertainment\x10\x03\x12\n\n\x06Sports\x10\x04\x12\x0b\
+    \n\x07Science\x10\x05\x12\t\n\x05Other\x10\x06b\x06proto3\
 ";
 
 static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
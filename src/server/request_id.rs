@@ -0,0 +1,98 @@
+//! Request ids, so the log lines of a single request can be tied together
+//!
+//! A client(or the proxy in front of us) can send its own id in the `X-Request-Id` header, otherwise
+//! we make one up. Either way it's echoed back in the response and attached to logs as `request_id`
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+
+const REQUEST_ID: &str = "X-Request-Id";
+/// Longest id we accept from a client, anything longer gets replaced with ours
+const MAX_LENGTH: usize = 128;
+
+lazy_static! {
+    /// Ids are `<server start>-<counter>` in hex, so they don't repeat across restarts
+    static ref STARTED: u64 = UNIX_EPOCH.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+}
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The id of the current request
+#[derive(Clone, Debug)]
+pub struct RequestId(String);
+
+impl RequestId {
+    fn generate() -> RequestId {
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        RequestId(format!("{:x}-{:x}", *STARTED, count))
+    }
+    /// Use the client's id if it looks sane, otherwise generate one
+    fn from_header(header: Option<&str>) -> RequestId {
+        match header {
+            Some(id)
+                if !id.is_empty()
+                    && id.len() <= MAX_LENGTH
+                    && id.chars().all(|c| c.is_ascii_graphic()) =>
+            {
+                RequestId(id.to_string())
+            }
+            _ => RequestId::generate(),
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+    /// The id of one item of a batch sent with this request, `<id>.<index>`
+    pub fn item(&self, index: usize) -> RequestId {
+        RequestId(format!("{}.{}", self.0, index))
+    }
+    /// The id of a request, cached so every caller sees the same one
+    pub fn of<'r>(request: &'r Request<'_>) -> &'r RequestId {
+        request.local_cache(|| RequestId::from_header(request.headers().get_one(REQUEST_ID)))
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for RequestId {
+    type Error = ();
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        rocket::outcome::Outcome::Success(RequestId::of(request).clone())
+    }
+}
+
+/// A fairing assigning every request an id and echoing it in the response
+pub struct RequestIds;
+
+#[rocket::async_trait]
+impl Fairing for RequestIds {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ids",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data) {
+        RequestId::of(request);
+    }
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let id = RequestId::of(request);
+        debug!(
+            request_id = id.as_str(),
+            method = request.method().as_str(),
+            path = request.uri().path(),
+            status = response.status().code;
+            "Handled request"
+        );
+        response.set_header(Header::new(REQUEST_ID, id.to_string()));
+    }
+}
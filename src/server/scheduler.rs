@@ -0,0 +1,244 @@
+//! Runs the server's periodic background jobs
+//!
+//! Each job gets its own thread which sleeps on a condition variable between runs, so a job can be
+//! woken early to run now(`POST /admin/jobs/<name>`) or to stop(`Scheduler::shutdown`).
+//! Intervals come from `config::JobsConfig`, `GET /admin/jobs` shows when each job last ran and how long it
+//! took. Jobs log their own errors, a panicking job takes the server down(release builds abort on panic)
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rocket::http::Status;
+use rocket::State;
+use serde::Serialize;
+
+use crate::server::config::JobConfig;
+use crate::server::top::PrettyJson;
+
+/// What `/admin/jobs` reports for a job, times are unix seconds
+#[derive(Clone, Serialize)]
+pub struct JobStatus {
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub config: JobConfig,
+    pub running: bool,
+    pub runs: u64,
+    pub last_started: Option<u64>,
+    pub last_duration_secs: Option<f64>,
+    pub next_run: Option<u64>,
+}
+
+struct Job {
+    task: Box<dyn Fn() + Send + Sync>,
+    status: Mutex<JobStatus>,
+    /// Set when the job has been asked to run now
+    triggered: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl Job {
+    /// How long to wait before the next run, `None` if the job only runs when triggered
+    fn next_wait(&self, config: JobConfig) -> Option<Duration> {
+        if !config.enabled {
+            return None;
+        }
+        let jitter = if config.jitter == 0 {
+            0
+        } else {
+            // not cryptographic, just enough so jobs with the same interval drift apart
+            let mut hasher = DefaultHasher::new();
+            SystemTime::now().hash(&mut hasher);
+            config.interval.hash(&mut hasher);
+            self.status.lock().unwrap().name.hash(&mut hasher);
+            hasher.finish() % (config.jitter + 1)
+        };
+        Some(Duration::from_secs(config.interval + jitter))
+    }
+    fn run(&self) {
+        let name = {
+            let mut status = self.status.lock().unwrap();
+            status.running = true;
+            status.next_run = None;
+            status.last_started = Some(unix_now());
+            status.name
+        };
+        let start = Instant::now();
+        (self.task)();
+        let duration = start.elapsed().as_secs_f64();
+        debug!(job = name, duration = duration; "Job finished");
+        let mut status = self.status.lock().unwrap();
+        status.running = false;
+        status.runs += 1;
+        status.last_duration_secs = Some(duration);
+    }
+}
+
+fn unix_now() -> u64 {
+    UNIX_EPOCH.elapsed().map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[derive(Default)]
+struct Inner {
+    jobs: Vec<Arc<Job>>,
+    stopping: AtomicBool,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Collects jobs, `start` hands them to a `Scheduler`
+#[derive(Default)]
+pub struct SchedulerBuilder {
+    jobs: Vec<Arc<Job>>,
+}
+
+impl SchedulerBuilder {
+    pub fn job<F>(mut self, name: &'static str, config: JobConfig, task: F) -> SchedulerBuilder
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.jobs.push(Arc::new(Job {
+            task: Box::new(task),
+            status: Mutex::new(JobStatus {
+                name,
+                config,
+                running: false,
+                runs: 0,
+                last_started: None,
+                last_duration_secs: None,
+                next_run: None,
+            }),
+            triggered: Mutex::new(false),
+            wake: Condvar::new(),
+        }));
+        self
+    }
+    /// Spawn a thread for every job
+    pub fn start(self) -> Scheduler {
+        let scheduler = Scheduler(Arc::new(Inner {
+            jobs: self.jobs,
+            ..Inner::default()
+        }));
+        let mut threads = scheduler.0.threads.lock().unwrap();
+        for job in &scheduler.0.jobs {
+            let inner = scheduler.0.clone();
+            let job = job.clone();
+            let name = job.status.lock().unwrap().name;
+            let handle = std::thread::Builder::new()
+                .name(format!("job {}", name))
+                .spawn(move || job_loop(&inner, &job))
+                .expect("Could not spawn job thread");
+            threads.push(handle);
+        }
+        drop(threads);
+        scheduler
+    }
+}
+
+/// Wait for the next run(or a trigger) and run the job, until we are stopped
+fn job_loop(inner: &Inner, job: &Job) {
+    loop {
+        let config = job.status.lock().unwrap().config;
+        let deadline = job.next_wait(config).map(|wait| {
+            job.status.lock().unwrap().next_run = Some(unix_now() + wait.as_secs());
+            Instant::now() + wait
+        });
+        let mut triggered = job.triggered.lock().unwrap();
+        while !*triggered && !inner.stopping.load(Ordering::Acquire) {
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    triggered = job.wake.wait_timeout(triggered, deadline - now).unwrap().0;
+                }
+                None => triggered = job.wake.wait(triggered).unwrap(),
+            }
+        }
+        if inner.stopping.load(Ordering::Acquire) {
+            return;
+        }
+        *triggered = false;
+        drop(triggered);
+        job.run();
+    }
+}
+
+/// Handle to the running jobs, cheap to clone
+#[derive(Clone)]
+pub struct Scheduler(Arc<Inner>);
+
+impl Scheduler {
+    pub fn builder() -> SchedulerBuilder {
+        SchedulerBuilder::default()
+    }
+    fn find(&self, name: &str) -> Option<&Arc<Job>> {
+        self.0
+            .jobs
+            .iter()
+            .find(|job| job.status.lock().unwrap().name == name)
+    }
+    pub fn jobs(&self) -> Vec<JobStatus> {
+        self.0
+            .jobs
+            .iter()
+            .map(|job| job.status.lock().unwrap().clone())
+            .collect()
+    }
+    /// Ask a job to run now, if it's running it will run again once it's done
+    ///
+    /// Returns `false` if there is no such job
+    pub fn trigger(&self, name: &str) -> bool {
+        match self.find(name) {
+            Some(job) => {
+                *job.triggered.lock().unwrap() = true;
+                job.wake.notify_one();
+                info!(job = name; "Job triggered");
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn is_stopping(&self) -> bool {
+        self.0.stopping.load(Ordering::Acquire)
+    }
+    /// Stop all jobs, waiting for any that are running to finish
+    pub fn shutdown(&self) {
+        if self.0.stopping.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        for job in &self.0.jobs {
+            // take the lock so a job between checking `stopping` and waiting doesn't miss the wakeup
+            let _guard = job.triggered.lock().unwrap();
+            job.wake.notify_all();
+        }
+        let threads: Vec<_> = self.0.threads.lock().unwrap().drain(..).collect();
+        for thread in threads {
+            if thread.join().is_err() {
+                error!("A job thread panicked while shutting down");
+            }
+        }
+        info!("Background jobs stopped");
+    }
+}
+
+/// List background jobs and their last run
+#[get("/admin/jobs")]
+pub async fn list_jobs(scheduler: State<'_, Scheduler>) -> PrettyJson<Vec<JobStatus>> {
+    PrettyJson(scheduler.jobs())
+}
+
+/// Run a job now, `202` if it was triggered, `404` if there's no such job
+#[post("/admin/jobs/<name>")]
+pub async fn trigger_job(name: String, scheduler: State<'_, Scheduler>) -> Status {
+    if scheduler.is_stopping() {
+        return Status::ServiceUnavailable;
+    }
+    if scheduler.trigger(name.as_str()) {
+        Status::Accepted
+    } else {
+        Status::NotFound
+    }
+}
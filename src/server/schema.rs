@@ -0,0 +1,297 @@
+//! Layout of the sled DataBase and how to upgrade it
+//!
+//! Data lives in named trees
+//! * `articles`: `ProtoFile`s keyed by file name
+//! * `meta`: the schema version, the time to live(`TTL`) and the store generation(`GENERATION`)
+//! * `embeddings`, `threads`: see `snapshot.rs`
+//! * `timelines`: see `timeline.rs`
+//! * `sources`: see `source_stats.rs`
+//!
+//! The schema version in `meta` says which layout the store has, `check` refuses to run against a store
+//! from another version and `tgnews migrate` upgrades old ones in place
+//!
+//! Versions
+//! * `0`: articles and the `TTL` key mixed in the default tree, no version anywhere
+//! * `1`: named trees, `ProtoFile.version` set on every record
+//! * `2`: `ProtoFile.max_age` is -1 when we don't know it, 0 is a `max-age=0` article that expires right away
+use std::convert::TryInto;
+
+use protobuf::{parse_from_bytes, Message};
+use serde::Serialize;
+use sled::Tree;
+
+use crate::server::protos::server_files::ProtoFile;
+use crate::server::GLOBAL_DBASE;
+
+/// Version of the store layout this build reads and writes
+pub const SCHEMA_VERSION: u32 = 2;
+/// Key of the schema version in `meta`
+const VERSION_KEY: &[u8] = b"SCHEMA_VERSION";
+/// Key of the time to live in `meta`, and in the default tree before version 1
+pub const TTL_KEY: &[u8] = b"TTL";
+/// Key of the store generation in `meta`, and in `threads` before version 1
+pub const GENERATION_KEY: &[u8] = b"GENERATION";
+
+lazy_static! {
+    pub static ref ARTICLES: Tree = open("articles");
+    pub static ref META: Tree = open("meta");
+    pub static ref EMBEDDINGS: Tree = open("embeddings");
+    pub static ref THREADS: Tree = open("threads");
+    pub static ref TIMELINES: Tree = open("timelines");
+    pub static ref SOURCES: Tree = open("sources");
+}
+
+fn open(name: &str) -> Tree {
+    GLOBAL_DBASE
+        .open_tree(name)
+        .unwrap_or_else(|e| panic!("Could not open tree `{}`\n{}", name, e))
+}
+
+/// Read a big endian `u64` from `meta`
+pub fn read_u64(key: &[u8]) -> Option<u64> {
+    match META.get(key) {
+        Ok(Some(value)) => value.as_ref().try_into().ok().map(u64::from_be_bytes),
+        Ok(None) => None,
+        Err(e) => {
+            error!("Could not read `{}` from meta\n{}", String::from_utf8_lossy(key), e);
+            None
+        }
+    }
+}
+
+/// The layout version of the store
+///
+/// A store without a version is either empty(so it's whatever we write) or from before versions
+pub fn stored_version() -> u32 {
+    match META.get(VERSION_KEY) {
+        Ok(Some(value)) => value
+            .as_ref()
+            .try_into()
+            .map(u32::from_be_bytes)
+            .expect("Schema version is corrupted"),
+        _ if GLOBAL_DBASE.is_empty() && ARTICLES.is_empty() => SCHEMA_VERSION,
+        _ => 0,
+    }
+}
+
+fn set_version(version: u32) {
+    META.insert(VERSION_KEY, version.to_be_bytes().as_ref())
+        .expect("Could not write schema version");
+}
+
+/// Make sure the store has the layout we expect before the server touches it
+///
+/// # Panics
+/// If the store needs migrating, or was written by a newer build
+pub fn check() {
+    let version = stored_version();
+    if version < SCHEMA_VERSION {
+        panic!(
+            "The DataBase has schema version {} but we need {}, run `tgnews migrate` first",
+            version, SCHEMA_VERSION
+        );
+    }
+    if version > SCHEMA_VERSION {
+        panic!(
+            "The DataBase has schema version {} which is newer than ours({}), refusing to touch it",
+            version, SCHEMA_VERSION
+        );
+    }
+    set_version(version);
+}
+
+/// What a migration step did, or would do on a dry run
+#[derive(Serialize, Default)]
+pub struct StepReport {
+    pub from: u32,
+    pub to: u32,
+    pub description: &'static str,
+    /// Articles moved or rewritten
+    pub articles_moved: usize,
+    pub meta_keys_moved: Vec<String>,
+    /// Keys we couldn't parse, they are left where they were
+    pub unreadable: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub from: u32,
+    pub to: u32,
+    pub steps: Vec<StepReport>,
+}
+
+/// One upgrade, from version `from` to `from + 1`
+struct Migration {
+    from: u32,
+    description: &'static str,
+    run: fn(bool, &mut StepReport),
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Move articles and TTL out of the default tree, move GENERATION to meta, \
+                      set the record version",
+        run: split_default_tree,
+    },
+    Migration {
+        from: 1,
+        description: "Mark articles without a max-age with -1",
+        run: mark_unknown_max_age,
+    },
+];
+
+/// The `max_age` of a record, `None` if we don't know it
+///
+/// Before version 2 that was 0, since then it's -1
+#[allow(clippy::cast_sign_loss)]
+pub fn max_age(file: &ProtoFile) -> Option<u64> {
+    if file.max_age < 0 || (file.version < 2 && file.max_age == 0) {
+        None
+    } else {
+        Some(file.max_age as u64)
+    }
+}
+
+/// Bring a record written at an older version up to `SCHEMA_VERSION`
+pub fn upgrade(file: &mut ProtoFile) {
+    if max_age(file).is_none() {
+        file.set_max_age(-1);
+    }
+    file.set_version(SCHEMA_VERSION);
+}
+
+/// `0 -> 1`
+fn split_default_tree(dry_run: bool, report: &mut StepReport) {
+    for entry in GLOBAL_DBASE.iter() {
+        let (key, value) = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                error!("Could not read from the DataBase\n{}", e);
+                continue;
+            }
+        };
+        if key.as_ref() == TTL_KEY {
+            report.meta_keys_moved.push("TTL".to_string());
+            if !dry_run {
+                META.insert(TTL_KEY, value).expect("Could not move TTL");
+                GLOBAL_DBASE.remove(key).expect("Could not remove old TTL");
+            }
+            continue;
+        }
+        let mut file: ProtoFile = match parse_from_bytes(value.as_ref()) {
+            Ok(file) => file,
+            Err(_) => {
+                report
+                    .unreadable
+                    .push(String::from_utf8_lossy(key.as_ref()).to_string());
+                continue;
+            }
+        };
+        report.articles_moved += 1;
+        if !dry_run {
+            file.set_version(1);
+            ARTICLES
+                .insert(key.clone(), file.write_to_bytes().unwrap())
+                .expect("Could not move article");
+            GLOBAL_DBASE.remove(key).expect("Could not remove old article");
+        }
+    }
+    if let Ok(Some(generation)) = THREADS.get(GENERATION_KEY) {
+        report.meta_keys_moved.push("GENERATION".to_string());
+        if !dry_run {
+            META.insert(GENERATION_KEY, generation)
+                .expect("Could not move GENERATION");
+            THREADS
+                .remove(GENERATION_KEY)
+                .expect("Could not remove old GENERATION");
+        }
+    }
+}
+
+/// `1 -> 2`
+///
+/// A dry run from version 0 didn't move the articles, so it counts them where `split_default_tree` would
+/// have moved them from
+fn mark_unknown_max_age(dry_run: bool, report: &mut StepReport) {
+    let articles: &Tree = if dry_run && stored_version() == 0 {
+        &**GLOBAL_DBASE
+    } else {
+        &*ARTICLES
+    };
+    for entry in articles.iter() {
+        let (key, value) = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                error!("Could not read from the DataBase\n{}", e);
+                continue;
+            }
+        };
+        if key.as_ref() == TTL_KEY {
+            continue;
+        }
+        let mut file: ProtoFile = match parse_from_bytes(value.as_ref()) {
+            Ok(file) => file,
+            Err(_) => {
+                report
+                    .unreadable
+                    .push(String::from_utf8_lossy(key.as_ref()).to_string());
+                continue;
+            }
+        };
+        if file.version >= 2 {
+            continue;
+        }
+        report.articles_moved += 1;
+        if !dry_run {
+            upgrade(&mut file);
+            ARTICLES
+                .insert(key, file.write_to_bytes().unwrap())
+                .expect("Could not rewrite article");
+        }
+    }
+}
+
+/// Upgrade the store to `SCHEMA_VERSION`, one version at a time
+///
+/// On a dry run nothing is written, the report says what would happen. Each step records its version
+/// once it's done, so an interrupted migration picks up from the step that failed
+pub fn migrate(dry_run: bool) -> MigrationReport {
+    let from = stored_version();
+    let mut steps = vec![];
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from) {
+        let mut report = StepReport {
+            from: migration.from,
+            to: migration.from + 1,
+            description: migration.description,
+            ..StepReport::default()
+        };
+        (migration.run)(dry_run, &mut report);
+        if !dry_run {
+            set_version(migration.from + 1);
+            GLOBAL_DBASE.flush().expect("Could not flush the DataBase");
+        }
+        info!(
+            from = report.from,
+            to = report.to,
+            articles = report.articles_moved,
+            unreadable = report.unreadable.len(),
+            dry_run = dry_run;
+            "Migration step done"
+        );
+        steps.push(report);
+    }
+    MigrationReport {
+        dry_run,
+        from,
+        to: SCHEMA_VERSION,
+        steps,
+    }
+}
+
+/// Entry point for `tgnews migrate`, prints the report as JSON
+pub fn migrate_entry(dry_run: bool) {
+    let report = migrate(dry_run);
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
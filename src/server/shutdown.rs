@@ -0,0 +1,98 @@
+//! Graceful shutdown
+//!
+//! On SIGTERM or Ctrl-C we
+//! 1. Move to `Lifecycle::Draining`, so uploads and deletes get `503`, and end open thread streams
+//! 2. Wait for uploads still being classified on the global pool, up to `shutdown_timeout` seconds
+//! 3. Stop the background jobs
+//! 4. Save the threads of every clustered language at the current generation(see `snapshot`), clustering the
+//! articles uploaded since the last clustering first, so the next start restores them instead of clustering again
+//! 5. Flush the DataBase, it holds every article, their embeddings and those threads
+//! 6. Tell Rocket to stop
+use std::time::{Duration, Instant};
+
+use rocket::Shutdown;
+use whatlang::Lang;
+
+use crate::server::events;
+use crate::server::scheduler::Scheduler;
+use crate::server::state::{Lifecycle, ServerState};
+use crate::server::static_pools::{flush_dirty_io, in_flight};
+use crate::server::{lang_code, EN_CLUSTERS, RU_CLUSTERS};
+
+/// How often we check whether the pool has drained
+const DRAIN_POLL: Duration = Duration::from_millis(100);
+
+/// Resolves on the first SIGTERM or Ctrl-C
+async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {},
+                    _ = tokio::signal::ctrl_c() => {},
+                }
+                return;
+            }
+            Err(e) => error!("Could not listen for SIGTERM, only handling Ctrl-C\n{}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Could not listen for Ctrl-C, the server won't shut down gracefully\n{}", e);
+        futures::future::pending::<()>().await;
+    }
+}
+
+/// Wait for every in flight upload to finish, returns how many were left when we gave up
+async fn drain(timeout: Duration) -> usize {
+    let start = Instant::now();
+    loop {
+        let remaining = in_flight();
+        if remaining == 0 || start.elapsed() >= timeout {
+            return remaining;
+        }
+        tokio::time::delay_for(DRAIN_POLL).await;
+    }
+}
+
+/// Everything that must happen before the process exits, safe to call more than once
+///
+/// This blocks, waiting for running jobs to finish. Threads of a language are only saved once it was
+/// clustered, before that its clusters may not hold every article
+pub fn persist(state: &ServerState, scheduler: &Scheduler) {
+    scheduler.shutdown();
+    for &(lang, clusters) in [(Lang::Eng, &*EN_CLUSTERS), (Lang::Rus, &*RU_CLUSTERS)].iter() {
+        if state.progress(lang).is_clustered() {
+            clusters.write().unwrap().save_snapshot();
+            info!(lang = lang_code(lang); "Threads saved");
+        }
+    }
+    flush_dirty_io();
+    info!("DBASE flushed");
+}
+
+/// Wait for a signal then shut down, see the module docs for the steps
+pub async fn on_signal(
+    state: ServerState,
+    scheduler: Scheduler,
+    timeout: Duration,
+    rocket: Shutdown,
+) {
+    signal().await;
+    info!(timeout = timeout.as_secs(); "Shutting down");
+    state.advance(Lifecycle::Draining);
+    events::close_all();
+
+    let start = Instant::now();
+    let remaining = drain(timeout).await;
+    if remaining == 0 {
+        info!(duration = start.elapsed().as_secs_f64(); "Uploads drained");
+    } else {
+        warn!(remaining = remaining; "Timed out waiting for uploads, they will be lost");
+    }
+    if let Err(e) = tokio::task::spawn_blocking(move || persist(&state, &scheduler)).await {
+        error!("Could not persist state before shutting down\n{}", e);
+    }
+    rocket.notify();
+}
@@ -0,0 +1,183 @@
+//! Cluster state that survives restarts
+//!
+//! Two kinds of blobs are kept in the store next to the articles
+//! * `embeddings`: the embedding of every article(see `embeddings.rs`), tagged with the article's content
+//! hash and the representation it's in so an updated article, or a change of representation, embeds it again.
+//! Rebuilding only embeds articles we haven't seen, bodies aren't stored so those get only their title and are
+//! tagged as title embeddings. The TF-IDF document frequencies(see `embeddings.rs`) are kept here too, written
+//! by the `flush_dirty_io` job and read on start
+//! * `threads`: a `ClusterSnapshot` per language, written after every clustering
+//!
+//! The store has a generation which is bumped whenever articles are stored or removed.
+//! A snapshot taken at the current generation is used as is. An older one(the usual case on a live server,
+//! where uploads keep coming) only seeds the threads we link the next clustering against, so thread ids and
+//! history survive the restart, see `SClusterer::restore`
+use std::convert::TryInto;
+
+use protobuf::{parse_from_bytes, Message};
+use whatlang::Lang;
+
+use crate::embeddings::{
+    embed, representation, restore_frequencies, take_frequencies, Representation,
+};
+use crate::server::enums::HTMLData;
+use crate::server::protos::server_files::ClusterSnapshot;
+use crate::server::lang_code;
+use crate::server::store::{store, Blobs};
+use crate::threads::{EN_VECTORS, RU_VECTORS};
+use crate::utils::clean;
+
+/// Where the TF-IDF document frequencies are kept among the embeddings, no article can be named this
+const FREQUENCIES_KEY: &str = "/tfidf";
+
+/// The current store generation
+pub fn generation() -> u64 {
+    store().generation()
+}
+/// Mark the store as changed, invalidating snapshots
+///
+/// Call it after the change has reached both the DataBase and the clusters
+pub fn bump_generation() {
+    store().bump_generation()
+}
+/// What an embedding in `representation` of an article is stored under
+fn tag(representation: Representation, article: &HTMLData) -> String {
+    match representation {
+        // title embeddings keep the bare hash, they were stored before there was a choice
+        Representation::Title => article.content_hash.clone(),
+        other => format!("{}:{}", other.name(), article.content_hash),
+    }
+}
+/// The embedding of an article, computed only if we don't have it stored
+///
+/// Articles read back from the DataBase have no body, they keep the embedding stored when they were
+/// uploaded and only fall back to their title if there is none
+pub fn embedding(article: &HTMLData) -> Vec<f32> {
+    let key = article.file_name.as_str();
+    let stored = store().get_blob(Blobs::Embeddings, key).ok().flatten();
+    if let Some(value) = stored.as_ref() {
+        if let Some(embedding) = decode(value, tag(representation(), article).as_str()) {
+            return embedding;
+        }
+        // without a body every representation falls back to the title
+        if article.body.trim().is_empty() {
+            if let Some(embedding) = decode(value, tag(Representation::Title, article).as_str()) {
+                return embedding;
+            }
+        }
+    }
+    let (embedding, used) = match article.lang.unwrap() {
+        Lang::Eng => embed(
+            &EN_VECTORS,
+            clean(article.title.clone(), true).as_str(),
+            article.body.as_str(),
+        ),
+        Lang::Rus => embed(
+            &RU_VECTORS,
+            clean(article.title.clone(), false).as_str(),
+            article.body.as_str(),
+        ),
+        _ => unreachable!(),
+    };
+    if used != representation() {
+        debug!(
+            article = key,
+            representation = representation().name();
+            "Embedded by the title alone"
+        );
+        // a body aware embedding of this version, from another representation, is worth more
+        let body_aware = stored.as_ref().map_or(false, |value| {
+            stored_tag(value).map_or(false, |tag| {
+                tag.ends_with(format!(":{}", article.content_hash).as_str())
+            })
+        });
+        if body_aware {
+            return embedding;
+        }
+    }
+    let value = encode(&embedding, tag(used, article).as_str());
+    if let Err(e) = store().put_blob(Blobs::Embeddings, key, &value) {
+        error!(article = article.file_name.as_str(); "Could not store embedding\n{}", e);
+    }
+    embedding
+}
+/// Stored embeddings are `[hash length][content hash][f32, little endian]...`
+fn encode(embedding: &[f32], hash: &str) -> Vec<u8> {
+    let mut value = Vec::with_capacity(1 + hash.len() + embedding.len() * 4);
+    value.push(hash.len() as u8);
+    value.extend_from_slice(hash.as_bytes());
+    embedding
+        .iter()
+        .for_each(|f| value.extend_from_slice(&f.to_le_bytes()));
+    value
+}
+/// The tag a stored embedding was saved under, see `tag`
+fn stored_tag(value: &[u8]) -> Option<&str> {
+    let hash_len = *value.first()? as usize;
+    std::str::from_utf8(value.get(1..=hash_len)?).ok()
+}
+/// Decode a stored embedding, `None` if it's for another version of the article
+fn decode(value: &[u8], hash: &str) -> Option<Vec<f32>> {
+    let hash_len = *value.first()? as usize;
+    let stored_hash = value.get(1..=hash_len)?;
+    if stored_hash != hash.as_bytes() {
+        return None;
+    }
+    let floats = &value[1 + hash_len..];
+    if floats.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        floats
+            .chunks_exact(4)
+            .map(|f| f32::from_le_bytes(f.try_into().unwrap()))
+            .collect(),
+    )
+}
+/// Store the TF-IDF document frequencies, if they changed since they were last stored
+pub fn save_frequencies() {
+    if let Some(value) = take_frequencies() {
+        if let Err(e) = store().put_blob(Blobs::Embeddings, FREQUENCIES_KEY, &value) {
+            error!("Could not store document frequencies\n{}", e);
+        }
+    }
+}
+/// Weigh against the stored TF-IDF document frequencies, if there are any
+pub fn load_frequencies() {
+    if let Ok(Some(value)) = store().get_blob(Blobs::Embeddings, FREQUENCIES_KEY) {
+        if let Err(e) = restore_frequencies(&value) {
+            warn!("Ignoring unreadable document frequencies\n{}", e);
+        }
+    }
+}
+/// Drop the stored embedding of a removed article
+pub fn forget(file: &str) {
+    if let Err(e) = store().delete_blob(Blobs::Embeddings, file) {
+        error!(article = file; "Could not remove embedding\n{}", e);
+    }
+}
+/// Store the threads of a language
+pub fn save(lang: Lang, snapshot: &ClusterSnapshot) {
+    let bytes = match snapshot.write_to_bytes() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!(lang = lang_code(lang); "Could not serialize cluster snapshot\n{}", e);
+            return;
+        }
+    };
+    if let Err(e) = store().put_blob(Blobs::Threads, lang_code(lang), &bytes) {
+        error!(lang = lang_code(lang); "Could not store cluster snapshot\n{}", e);
+    }
+}
+/// Load the threads of a language, however old they are
+pub fn load(lang: Lang) -> Option<ClusterSnapshot> {
+    let value = store().get_blob(Blobs::Threads, lang_code(lang)).ok()??;
+    let snapshot: ClusterSnapshot = match parse_from_bytes(&value) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!(lang = lang_code(lang); "Ignoring unreadable cluster snapshot\n{}", e);
+            return None;
+        }
+    };
+    Some(snapshot)
+}
@@ -0,0 +1,311 @@
+//! What each host's articles tell us about it
+//!
+//! Per host we count
+//! * new articles, and how many of them were rejected as not news by the news filter or the category
+//!  classifier(see `upload::ingest`). Articles that expired before being stored aren't counted
+//! * threads it was in, how many of them it reported first(it had the oldest article) and how many were
+//!  large(at least `LARGE_THREAD` articles)
+//!
+//! Threads are counted per thread id(see `history.rs`) after every clustering, a live thread is kept as a
+//! `ThreadShare` and moves into the counts once it's gone so reclustering doesn't count it twice.
+//! Stats are stored next to the articles(blob `sources`) so they survive restarts. Uploads are counted in memory
+//! and written out by the `flush_dirty_io` job, so an upload never waits on the store.
+//!
+//! From them every host gets a reliability between 0 and 1, see `SourceReport::reliability`, which the ranking
+//! can weigh next to the static authority(`reliability` in `RankingConfig`).
+//!
+//! `GET /sources/<host>` returns a host's stats
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+
+use protobuf::{parse_from_bytes, Message, RepeatedField};
+use rocket::http::Status;
+use serde::Serialize;
+use whatlang::Lang;
+
+use crate::server::cluster::AllArticles;
+use crate::server::lang_code;
+use crate::server::protos::server_files::{SourceStats, ThreadShare};
+use crate::server::store::{store, Blobs};
+use crate::server::top::PrettyJson;
+use crate::sources::registry;
+use crate::utils::host;
+
+/// Threads with at least this many articles are large
+pub const LARGE_THREAD: usize = 10;
+/// Threads a host needs to be in before its reliability counts fully, fewer pull it towards 0
+const PRIOR_THREADS: f64 = 10.0;
+
+lazy_static! {
+    /// Held while stats are read, changed and written back
+    static ref UPDATING: Mutex<()> = Mutex::new(());
+    /// Uploads counted since the last `persist`, by host
+    static ref PENDING: Mutex<HashMap<String, Uploads>> = Mutex::new(HashMap::new());
+    /// Reliability by host, so ranking doesn't go to the store for every host
+    static ref RELIABILITY: RwLock<HashMap<String, f64>> = RwLock::new(HashMap::new());
+}
+
+fn load(host: &str) -> Option<SourceStats> {
+    let value = store().get_blob(Blobs::Sources, host).ok()??;
+    match parse_from_bytes(&value) {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            warn!(host = host; "Ignoring unreadable source stats\n{}", e);
+            None
+        }
+    }
+}
+
+pub fn save(stats: &SourceStats) {
+    RELIABILITY.write().unwrap().insert(
+        stats.host.clone(),
+        SourceReport::from_stats(stats).reliability,
+    );
+    let result = stats
+        .write_to_bytes()
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| {
+            store()
+                .put_blob(Blobs::Sources, stats.host.as_str(), &bytes)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        error!(host = stats.host.as_str(); "Could not store source stats\n{}", e);
+    }
+}
+
+fn empty(host: &str) -> SourceStats {
+    let mut stats = SourceStats::new();
+    stats.set_host(host.to_string());
+    stats
+}
+
+fn load_or_new(host: &str) -> SourceStats {
+    load(host).unwrap_or_else(|| empty(host))
+}
+
+/// Uploads of a host not yet in the store
+#[derive(Default, Clone, Copy)]
+struct Uploads {
+    articles: u64,
+    not_news: u64,
+}
+
+impl Uploads {
+    fn add_to(self, stats: &mut SourceStats) {
+        stats.set_articles(stats.articles + self.articles);
+        stats.set_not_news(stats.not_news + self.not_news);
+    }
+}
+
+/// Count a new article from `url`, `news` is whether it was stored as news
+///
+/// This only touches memory, see `persist`
+pub fn record_upload(url: &str, news: bool) {
+    let host = match host(url) {
+        Some(host) => host,
+        None => return,
+    };
+    let mut pending = PENDING.lock().unwrap();
+    let uploads = pending.entry(host).or_default();
+    uploads.articles += 1;
+    if !news {
+        uploads.not_news += 1;
+    }
+}
+
+/// Write the uploads counted since the last call to the store
+pub fn persist() {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap());
+    if pending.is_empty() {
+        return;
+    }
+    let _updating = UPDATING.lock().unwrap();
+    for (host, uploads) in pending {
+        let mut stats = load_or_new(host.as_str());
+        uploads.add_to(&mut stats);
+        save(&stats);
+    }
+}
+
+/// The share each host has in `threads`, by host
+fn shares(threads: &[AllArticles]) -> HashMap<String, Vec<ThreadShare>> {
+    let mut shares: HashMap<String, Vec<ThreadShare>> = HashMap::new();
+    for thread in threads.iter().filter(|thread| !thread.id.is_empty()) {
+        let large = thread.articles.len() >= LARGE_THREAD;
+        // whoever had the oldest article reported it first
+        let first = thread
+            .times
+            .iter()
+            .zip(thread.hosts.iter())
+            .min_by_key(|(&time, _)| time)
+            .map(|(_, host)| host.as_str());
+        let hosts: HashSet<&str> = thread
+            .hosts
+            .iter()
+            .map(String::as_str)
+            .filter(|host| !host.is_empty())
+            .collect();
+        for host in hosts {
+            let mut share = ThreadShare::new();
+            share.set_thread(thread.id.clone());
+            share.set_first(first == Some(host));
+            share.set_large(large);
+            shares.entry(host.to_string()).or_default().push(share);
+        }
+    }
+    shares
+}
+
+/// Record the threads of a clustering of `lang`
+///
+/// Threads of `lang` a host was in but isn't any more are counted, the rest replace what we knew.
+/// This goes through every host we have stats for, there are a few thousand at most
+pub fn update(lang: Lang, threads: &[AllArticles]) {
+    let mut current = shares(threads);
+    // thread ids start with their language
+    let prefix = format!("{}-", lang_code(lang));
+    persist();
+    let _updating = UPDATING.lock().unwrap();
+    let mut hosts: HashSet<String> = match store().blob_keys(Blobs::Sources) {
+        Ok(keys) => keys.into_iter().collect(),
+        Err(e) => {
+            error!("Could not list source stats\n{}", e);
+            HashSet::new()
+        }
+    };
+    hosts.extend(current.keys().cloned());
+    for host in hosts {
+        let mut stats = load_or_new(host.as_str());
+        let latest = current.remove(&host).unwrap_or_default();
+        let ids: HashSet<&str> = latest.iter().map(|share| share.thread.as_str()).collect();
+        let before = stats.live.clone();
+        let mut live = vec![];
+        for share in stats.take_live() {
+            if !share.thread.starts_with(prefix.as_str()) {
+                live.push(share);
+            } else if !ids.contains(share.thread.as_str()) {
+                // gone, it counts from now on
+                stats.set_threads(stats.threads + 1);
+                if share.first {
+                    stats.set_first_reports(stats.first_reports + 1);
+                }
+                if share.large {
+                    stats.set_large_threads(stats.large_threads + 1);
+                }
+            }
+        }
+        live.extend(latest);
+        stats.set_live(RepeatedField::from_vec(live));
+        if stats.live != before {
+            save(&stats);
+        } else if !RELIABILITY.read().unwrap().contains_key(&host) {
+            // first clustering since a restart
+            let reliability = SourceReport::from_stats(&stats).reliability;
+            RELIABILITY.write().unwrap().insert(host, reliability);
+        }
+    }
+}
+
+/// Every host's stats, for exports
+pub fn all() -> Vec<SourceStats> {
+    persist();
+    match store().blob_keys(Blobs::Sources) {
+        Ok(keys) => keys.iter().filter_map(|key| load(key.as_str())).collect(),
+        Err(e) => {
+            error!("Could not list source stats\n{}", e);
+            vec![]
+        }
+    }
+}
+
+/// How reliable a host has been, 0 for hosts we know nothing about, see `SourceReport::reliability`
+pub fn reliability(host: &str) -> f64 {
+    RELIABILITY
+        .read()
+        .unwrap()
+        .get(host)
+        .copied()
+        .unwrap_or(0.0)
+}
+
+#[derive(Serialize)]
+pub struct SourceReport {
+    pub host: String,
+    /// Authority from the rating files, see `sources.rs`
+    pub authority: f64,
+    pub articles: u64,
+    pub not_news: u64,
+    /// Threads it was in, gone or alive
+    pub threads: u64,
+    /// Threads it's in now
+    pub live_threads: u64,
+    pub first_reports: u64,
+    pub large_threads: u64,
+    pub news_share: f64,
+    pub first_share: f64,
+    pub large_share: f64,
+    /// `news_share × (first_share + large_share) / 2`, pulled towards 0 for hosts in few threads
+    pub reliability: f64,
+}
+
+impl SourceReport {
+    #[allow(clippy::cast_precision_loss)]
+    fn from_stats(stats: &SourceStats) -> SourceReport {
+        let live_threads = stats.live.len() as u64;
+        let threads = stats.threads + live_threads;
+        let first_reports =
+            stats.first_reports + stats.live.iter().filter(|share| share.first).count() as u64;
+        let large_threads =
+            stats.large_threads + stats.live.iter().filter(|share| share.large).count() as u64;
+        let share = |part: u64, whole: u64| {
+            if whole == 0 {
+                0.0
+            } else {
+                part as f64 / whole as f64
+            }
+        };
+        // hosts we only know from threads haven't been rejected yet
+        let news_share = if stats.articles == 0 {
+            1.0
+        } else {
+            share(
+                stats.articles - stats.not_news.min(stats.articles),
+                stats.articles,
+            )
+        };
+        let first_share = share(first_reports, threads);
+        let large_share = share(large_threads, threads);
+        let confidence = threads as f64 / (threads as f64 + PRIOR_THREADS);
+        SourceReport {
+            host: stats.host.clone(),
+            authority: registry().authority(stats.host.as_str()),
+            articles: stats.articles,
+            not_news: stats.not_news,
+            threads,
+            live_threads,
+            first_reports,
+            large_threads,
+            news_share,
+            first_share,
+            large_share,
+            reliability: news_share * confidence * (first_share + large_share) / 2.0,
+        }
+    }
+}
+
+/// What we've seen of a host, `404` for hosts we have no stats for
+#[get("/sources/<site>")]
+pub async fn get_source(site: String) -> Result<PrettyJson<SourceReport>, Status> {
+    let site = site.trim_start_matches("www.").to_lowercase();
+    let pending = PENDING.lock().unwrap().get(&site).copied();
+    let mut stats = match load(site.as_str()) {
+        Some(stats) => stats,
+        None if pending.is_some() => empty(site.as_str()),
+        None => return Err(Status::NotFound),
+    };
+    if let Some(uploads) = pending {
+        uploads.add_to(&mut stats);
+    }
+    Ok(PrettyJson(SourceReport::from_stats(&stats)))
+}
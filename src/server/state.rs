@@ -0,0 +1,154 @@
+//! The server's lifecycle
+//!
+//! The server moves through `Starting -> Rebuilding -> Clustering -> Ready -> Draining` and never goes
+//! back, routes check where we are before touching the clusters.
+//!
+//! A `ServerState` is held in Rocket's managed state and cloned into the static pools that drive it forward
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use rocket::http::Status;
+use serde::Serialize;
+use whatlang::Lang;
+
+use crate::server::{EN_CLUSTERS, RU_CLUSTERS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lifecycle {
+    /// Server is up but we haven't started reading the DataBase
+    Starting,
+    /// Reading articles from the DataBase into the clusters
+    Rebuilding,
+    /// All articles are loaded, waiting for the first clustering of both languages
+    Clustering,
+    /// Both languages have been clustered
+    Ready,
+    /// Shutting down, no more uploads or deletes
+    Draining,
+}
+
+/// Progress of one language's clusterer
+///
+/// This is updated by `SClusterer` itself but kept outside its lock so that health checks
+/// don't block while we are clustering
+#[derive(Default)]
+pub struct LanguageProgress {
+    articles_loaded: AtomicUsize,
+    clusters_built: AtomicUsize,
+    clustered: AtomicBool,
+}
+
+impl LanguageProgress {
+    pub fn set_articles(&self, articles: usize) {
+        self.articles_loaded.store(articles, Ordering::Relaxed)
+    }
+    pub fn set_clusters(&self, clusters: usize) {
+        self.clusters_built.store(clusters, Ordering::Relaxed)
+    }
+    pub fn is_clustered(&self) -> bool {
+        self.clustered.load(Ordering::Acquire)
+    }
+    pub fn report(&self) -> ProgressReport {
+        ProgressReport {
+            articles_loaded: self.articles_loaded.load(Ordering::Relaxed),
+            clusters_built: self.clusters_built.load(Ordering::Relaxed),
+            clustered: self.is_clustered(),
+        }
+    }
+}
+
+/// A snapshot of `LanguageProgress` for the health endpoints
+#[derive(Serialize)]
+pub struct ProgressReport {
+    pub articles_loaded: usize,
+    pub clusters_built: usize,
+    pub clustered: bool,
+}
+
+struct Inner {
+    lifecycle: RwLock<Lifecycle>,
+    en: Arc<LanguageProgress>,
+    ru: Arc<LanguageProgress>,
+}
+
+#[derive(Clone)]
+pub struct ServerState(Arc<Inner>);
+
+impl Default for ServerState {
+    fn default() -> Self {
+        ServerState::new()
+    }
+}
+
+impl ServerState {
+    pub fn new() -> ServerState {
+        ServerState(Arc::new(Inner {
+            lifecycle: RwLock::new(Lifecycle::Starting),
+            en: EN_CLUSTERS.read().unwrap().progress(),
+            ru: RU_CLUSTERS.read().unwrap().progress(),
+        }))
+    }
+    pub fn lifecycle(&self) -> Lifecycle {
+        *self.0.lifecycle.read().unwrap()
+    }
+    /// Move the server to `next`
+    ///
+    /// The lifecycle only moves forward, so the periodic rebuilds don't make a ready server unready
+    pub fn advance(&self, next: Lifecycle) {
+        let mut lifecycle = self.0.lifecycle.write().unwrap();
+        if next > *lifecycle {
+            info!("Server lifecycle {:?} -> {:?}", *lifecycle, next);
+            *lifecycle = next;
+        }
+    }
+    pub fn progress(&self, lang: Lang) -> &LanguageProgress {
+        match lang {
+            Lang::Rus => &self.0.ru,
+            _ => &self.0.en,
+        }
+    }
+    /// Mark a language as clustered, once both are the server is ready
+    pub fn set_clustered(&self, lang: Lang) {
+        self.progress(lang).clustered.store(true, Ordering::Release);
+        if self.0.en.is_clustered() && self.0.ru.is_clustered() {
+            self.advance(Lifecycle::Ready);
+        }
+    }
+    /// Whether all articles have been read from the DataBase
+    pub fn is_rebuilt(&self) -> bool {
+        self.lifecycle() >= Lifecycle::Clustering
+    }
+    /// Check that we can take uploads and deletes
+    ///
+    /// We need the DataBase to be read and at least one language clustered
+    pub fn check_writable(&self) -> Result<(), Status> {
+        match self.lifecycle() {
+            Lifecycle::Starting | Lifecycle::Rebuilding => {
+                warn!("Files not rebuilt from database, cannot process requests");
+                Err(Status::ServiceUnavailable)
+            }
+            Lifecycle::Draining => {
+                warn!("Server is shutting down, not accepting changes");
+                Err(Status::ServiceUnavailable)
+            }
+            _ if !self.0.en.is_clustered() && !self.0.ru.is_clustered() => {
+                warn!("Either English Clustering or Russian clustering not finished, sending not implemented");
+                Err(Status::ServiceUnavailable)
+            }
+            _ => Ok(()),
+        }
+    }
+    /// Check that we can serve threads for `lang`
+    pub fn check_readable(&self, lang: Lang) -> Result<(), Status> {
+        if !self.is_rebuilt() {
+            warn!("Files not rebuilt from database, cannot process requests");
+            return Err(Status::ServiceUnavailable);
+        }
+        if !self.progress(lang).is_clustered() {
+            warn!("{:?} clustering not finished, cannot handle requests for it now", lang);
+            return Err(Status::ServiceUnavailable);
+        }
+        Ok(())
+    }
+}
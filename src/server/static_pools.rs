@@ -1,25 +1,27 @@
 //! Contains function and lazy initialized statics for  dealing with Pooled executions eg
-//! stuff that should be run asynchronously in pools
+//! stuff that should be run asynchronously in pools, and the background jobs the scheduler runs
 //!
 //! Because rust async types are lazy(like me ) by default
-use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
-use colored::*;
 use futures::executor::{ThreadPool, ThreadPoolBuilder};
 use futures::Future;
-use protobuf::{parse_from_bytes, ProtobufResult};
 use whatlang::Lang;
 
+use crate::server::config::JobsConfig;
 use crate::server::enums::HTMLData;
-use crate::server::protos::server_files::ProtoFile;
-use crate::server::{EN_CLUSTERS, RU_CLUSTERS};
-use crate::server::{
-    FINISHED_CLUSTERING_EN, FINISHED_CLUSTERING_RU, FINISHED_REBUILDING, GLOBAL_DBASE,
-};
+use crate::server::protos::read_ttl;
+use crate::server::scheduler::Scheduler;
+use crate::server::snapshot;
+use crate::server::source_stats;
+use crate::server::timeline;
+use crate::server::state::{Lifecycle, ServerState};
+use crate::server::store::{store, StoreError};
+use crate::server::{lang_code, EN_CLUSTERS, RU_CLUSTERS};
 
 lazy_static! {
-    /// A Global thread-pool use this for running  every other stuff except STATIC POOLS
+    /// A Global thread-pool use this for running  every other stuff except background jobs
     ///
     /// Aka run temporary asynchronous programs on this one
     ///
@@ -29,204 +31,191 @@ lazy_static! {
         .name_prefix("Global pool")
         .create()
         .expect("Could not create pool");
-    /// Used for running STATIC POOLS aka stuff that will run forever
-    ///
-    /// Note: Not all processes run forever, some are dropped so there will be some extra pools
-    /// but still, DON'T USE IT(jokes I meant do not touch it)
-    ///
-    /// Pool size is 7
-    static ref STATIC_POOL:ThreadPool=ThreadPoolBuilder::new()
-        .pool_size(7)
-        .name_prefix("Static pool")
-        .create()
-        .expect("Could not create pool");
+}
+/// Futures spawned with `pool` that haven't finished yet
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a future as in flight until it's dropped, even if it panics
+struct InFlight;
+
+impl InFlight {
+    fn start() -> InFlight {
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 /// Run a future to completion in another thread other than the main one
 pub fn pool<Fut>(future: Fut)
 where
     Fut: Future<Output = ()> + Send + 'static,
 {
-    GLOBAL_POOL.spawn_ok(future)
+    let guard = InFlight::start();
+    GLOBAL_POOL.spawn_ok(async move {
+        future.await;
+        drop(guard);
+    })
 }
-/// A static pool for executing processes that will run forever
-pub fn static_pool<Fut>(future: Fut)
-where
-    Fut: Future<Output = ()> + Send + 'static,
-{
-    STATIC_POOL.spawn_ok(future)
+/// How many futures sent to `pool` are queued or running, i.e uploads still being classified
+pub fn in_flight() -> usize {
+    IN_FLIGHT.load(Ordering::SeqCst)
 }
 /// Flush all dirty IO to the Database,
 /// guaranteeing in case of a system failure all previous io calls can be recovered
-/// this is put in a static pool to prevent it from hogging the main thread
-pub async fn flush_dirty_io() {
-    loop {
-        if let Err(e) = GLOBAL_DBASE.flush_async().await {
-            error!("SLED ERROR, could not flush dirty io to file \n{}", e)
-        };
-        std::thread::sleep(std::time::Duration::from_secs(300))
-    }
+pub fn flush_dirty_io() {
+    source_stats::persist();
+    snapshot::save_frequencies();
+    if let Err(e) = store().flush() {
+        error!("STORE ERROR, could not flush dirty io to file \n{}", e)
+    };
 }
-/// Execute all static pools in the Server
-/// I.e all  the processes that will be running throughout the server
+/// Start everything that runs in the background of the Server
 ///
-/// Processes are
-/// * `rebuild`: to read articles from the DataBase
-/// * `cluster`: Clustering articles
-/// * `get_stats*`: Print Human friendly statistics to the terminal
-/// * `flush_dirty_io`: Flush input to the database so in case of a crush we can recover(pro tip: always back up)
-/// * `remove_stale_docs`:Remove documents that have expired
-/// * `cluster_at_random_times`:Clusters at random times(where random is 5 minutes)
-pub fn execute_static_pools() {
-    static_pool(rebuild_async());
-    static_pool(cluster());
-    static_pool(get_stats());
-    static_pool(get_stats_ru());
-    // Create a pool for flushing dirty io, run every 5 minutes
-    static_pool(flush_dirty_io());
-    // Remove Stale documents
-    static_pool(remove_stale_docs());
-    static_pool(cluster_at_random_times());
+/// Rebuilding from the DataBase and the first clustering happen once, on their own thread.
+/// Then there are the periodic jobs, run by the returned `Scheduler`
+/// * `flush_dirty_io`: Flush input to the database so in case of a crush we can recover(pro tip: always back up),
+/// with the upload counts of `source_stats` and the TF-IDF document frequencies
+/// * `remove_stale_docs`:Remove documents whose `max-age` has elapsed
+/// * `recluster`:Re-read the database and cluster again, this is how the clusters pick up everything
+/// that changed
+/// * `stats_en`,`stats_ru`: Log statistics for each language
+/// * `prune_timelines`: Drop the timelines of threads that expired a week ago
+pub fn start(state: ServerState, jobs: &JobsConfig) -> Scheduler {
+    let startup = state.clone();
+    std::thread::Builder::new()
+        .name("rebuild".to_string())
+        .spawn(move || {
+            rebuild_sync(&startup);
+            cluster(&startup);
+        })
+        .expect("Could not spawn rebuild thread");
+    Scheduler::builder()
+        .job("flush_dirty_io", jobs.flush_dirty_io, flush_dirty_io)
+        .job("remove_stale_docs", jobs.remove_stale_docs, remove_stale_docs)
+        .job("recluster", jobs.recluster, move || recluster(&state))
+        .job("stats_en", jobs.stats_en, || EN_CLUSTERS.read().unwrap().get_stats())
+        .job("stats_ru", jobs.stats_ru, || RU_CLUSTERS.read().unwrap().get_stats())
+        .job("prune_timelines", jobs.prune_timelines, timeline::prune)
+        .start()
 }
-/// Remove Stale documents from the Index
-async fn remove_stale_docs() {
-    loop {
-        sleep(Duration::from_secs(600));
-        if GLOBAL_DBASE.len() < 40_000{
-            continue
-        }
-        debug!("Starting DBASE iteration");
-        GLOBAL_DBASE.iter().for_each(|f| {
-            if let Ok((key, value)) = f {
-                // key is name, value is proto file
-                if let Ok(instance) = parse_from_bytes(value.as_ref()) {
-                    let to_html = HTMLData::from_proto(instance);
-                    if to_html.calc_decay(10_000.) > 5.0 {
-                        if let Err(e) = GLOBAL_DBASE.remove(key) {
-                            error!("Error Removing document '{}' \n {}", to_html.file_name, e);
-                        } else {
-                            warn!("Removed stale file `{}`", to_html.file_name);
-                        }
-                    }
-                }
+/// Remove expired documents from the Index
+///
+/// An article expires `max-age` seconds(from its Cache-Control header) after it was published,
+/// counted against the newest article we have, see `HTMLData::is_expired`
+fn remove_stale_docs() {
+    let newest = match read_ttl() {
+        Some(newest) => newest,
+        None => return,
+    };
+    debug!("Starting DBASE iteration");
+    // collect first, deleting while scanning is up to the backend
+    let mut expired = vec![];
+    let scanned = store().scan(&mut |result| {
+        if let Ok(instance) = result {
+            let to_html = HTMLData::from_proto(instance);
+            if to_html.is_expired(newest) {
+                expired.push(to_html);
             }
-        });
+        }
+    });
+    if let Err(e) = scanned {
+        error!("Could not scan the store for expired documents \n {}", e);
+    }
+    for to_html in expired {
+        if let Err(e) = store().delete(to_html.file_name.as_str()) {
+            error!("Error Removing document '{}' \n {}", to_html.file_name, e);
+        } else {
+            match to_html.lang {
+                Some(Lang::Rus) => RU_CLUSTERS.write().unwrap().remove(&to_html.file_name),
+                _ => EN_CLUSTERS.write().unwrap().remove(&to_html.file_name),
+            };
+            snapshot::forget(&to_html.file_name);
+            snapshot::bump_generation();
+            warn!(article = to_html.file_name.as_str(); "Removed expired file");
+        }
     }
 }
-fn rebuild_sync() {
+fn rebuild_sync(state: &ServerState) {
     let time_now = Instant::now();
-    // Flush all documents and restart
+    state.advance(Lifecycle::Rebuilding);
+    // Forget the articles and read them again, the threads stay so the next clustering can link to them
     EN_CLUSTERS.write().unwrap().flush();
     RU_CLUSTERS.write().unwrap().flush();
-    for i in GLOBAL_DBASE.iter() {
-        if let Ok((_, value)) = i {
-            let file: ProtobufResult<ProtoFile> = parse_from_bytes(value.as_ref());
-            if file.is_err() {
-                continue;
-            }
-            let html_file = HTMLData::from_proto(file.unwrap());
+    let mut unreadable = 0;
+    let scanned = store().scan(&mut |result| match result {
+        Ok(file) => {
+            let html_file = HTMLData::from_proto(file);
             match html_file.lang.unwrap() {
                 Lang::Rus => RU_CLUSTERS.write().unwrap().add(&html_file),
                 Lang::Eng => EN_CLUSTERS.write().unwrap().add(&html_file),
                 _ => (),
             }
         }
+        Err(StoreError::Corrupt(key)) => {
+            warn!(article = key.as_str(); "Skipping unreadable article");
+            unreadable += 1;
+        }
+        Err(e) => error!("Could not read article \n{}", e),
+    });
+    if let Err(e) = scanned {
+        error!("Could not scan the store \n{}", e);
     }
-    eprintln!(
-        "Finished reading `{}` files in  {} second(s)",
-        GLOBAL_DBASE.len().to_string().green(),
-        time_now.elapsed().as_secs().to_string().red()
+    info!(
+        files = store().len(),
+        unreadable = unreadable,
+        duration = time_now.elapsed().as_secs_f64();
+        "Finished reading files from the DBASE"
     );
-    // Haa unsafe code
-    unsafe {
-        FINISHED_REBUILDING = true;
-    }
-}
-/// Print English statistics to the command line
-async fn get_stats() {
-    loop {
-        sleep(Duration::from_secs(60));
-        EN_CLUSTERS.read().unwrap().get_stats();
-    }
-}
-/// Print Russian statistics to the command line
-async fn get_stats_ru() {
-    loop {
-        sleep(Duration::from_secs(70));
-        RU_CLUSTERS.read().unwrap().get_stats();
-    }
-}
-/// Rebuild the clusters from the database
-///
-/// Pro tip: It's not a full database but a Key-Value one named `sled` damn n may I say that it's on fire
-async fn rebuild_async() {
-    rebuild_sync();
-}
-/// Main pool for clustering documents
-///
-/// This is ran  once, when the server starts to cluster articles already in the database
-/// then dies of in a ret value in assembly stack
-///
-/// Thanks mate
-///
-/// There is another instance aka `cluster_every_five_minutes`(yes function name is on point) that clusters every 5 mins
-/// Head over there to see magic
-async fn cluster() {
-    loop {
-        unsafe {
-            if !FINISHED_REBUILDING {
-                // sleep 5 seconds and check again
-                sleep(Duration::from_secs(5));
-                continue;
+    // Reuse the threads from the last clustering if nothing changed since, otherwise keep their ids
+    for &(lang, clusters) in [(Lang::Eng, &*EN_CLUSTERS), (Lang::Rus, &*RU_CLUSTERS)].iter() {
+        let mut clusters = clusters.write().unwrap();
+        if let Some(snapshot) = snapshot::load(lang) {
+            if clusters.restore(snapshot) {
+                info!(lang = lang_code(lang); "Restored threads from snapshot");
             }
         }
-        crossbeam_utils::thread::scope(|f| {
-            f.spawn(move |_| cluster_en());
-            f.spawn(move |_| cluster_ru());
-        })
-        .expect("Could not spawn threads");
-        // we have finished clustering documents in the DBASE
-        break;
     }
+    state.advance(Lifecycle::Clustering);
+}
+/// Cluster both languages, each on its own thread
+fn cluster(state: &ServerState) {
+    crossbeam_utils::thread::scope(|f| {
+        f.spawn(|_| cluster_en(state));
+        f.spawn(|_| cluster_ru(state));
+    })
+    .expect("Could not spawn threads");
 }
 /// Cluster English articles
 /// # Arguments
 /// `rebuild_from_dbase`:Rebuild the whole cache from the Database
-fn cluster_en() {
+fn cluster_en(state: &ServerState) {
     let mut global_docs = EN_CLUSTERS.write().unwrap();
     global_docs.cluster();
-
-    unsafe { FINISHED_CLUSTERING_EN = true };
+    state.set_clustered(Lang::Eng);
 }
 /// Cluster Russian articles
 /// # Arguments
 /// `rebuild_from_dbase`:Okay am Just being lazy, it's me reading every 5 mins the DataBase(takes about 5 seconds
 /// for 29000 files in an SSD) and re-clustering from there, this is to reflect on article deletions and such shenanigans because I have NO TIME
 /// TO DELETE MATRICES dynamically( am not mad)
-fn cluster_ru() {
+fn cluster_ru(state: &ServerState) {
     let mut global = RU_CLUSTERS.write().unwrap();
     global.cluster();
-    unsafe { FINISHED_CLUSTERING_RU = true };
+    state.set_clustered(Lang::Rus);
 }
-/// CLuster items every 5 minutes forever
+/// Re-read the DataBase and cluster again
 ///
 /// Sadly, there is no magic :*(
-async fn cluster_at_random_times() {
-    loop {
-        unsafe {
-            // if we haven't finished rebuilding, sleep for 10 seconds
-            if !FINISHED_REBUILDING {
-                sleep(Duration::from_secs(10));
-                continue;
-            }
-            sleep(Duration::from_secs(600));
-
-            rebuild_sync();
-            crossbeam_utils::thread::scope(|f| {
-                f.spawn(move |_| cluster_en());
-                f.spawn(move |_| cluster_ru());
-            })
-            .expect("Could not spawn lazy threads for our 5 minute clustering");
-            eprintln!("10 minute clustering done see you in 10 minutes");
-        }
+fn recluster(state: &ServerState) {
+    // the first rebuild hasn't finished, it'll cluster when it's done
+    if !state.is_rebuilt() {
+        return;
     }
+    rebuild_sync(state);
+    cluster(state);
 }
@@ -0,0 +1,127 @@
+//! Where articles are persisted
+//!
+//! Everything goes through the `ArticleStore` trait so the server doesn't care which database is behind it.
+//! There are three
+//! * `sled`(the default): the embedded key-value store we always had, see `schema.rs` for its layout
+//! * `memory`: nothing survives a restart, for tests and trying things out
+//! * `sqlite`: articles in a table with their title, url, language, category and date as columns,
+//! so analysts can query them with plain SQL
+//!
+//! The backend is picked with the `store` setting, see `config::StoreConfig`
+use std::fmt;
+
+use rocket::config::Config;
+
+use crate::server::config::{Backend, ServerConfig, StoreConfig};
+use crate::server::protos::server_files::ProtoFile;
+
+mod memory;
+mod sled_store;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use sled_store::SledStore;
+pub use sqlite::SqliteStore;
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The database itself failed
+    Backend(String),
+    /// A record is there but we can't read it, carries its key
+    Corrupt(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Backend(e) => write!(f, "Store error: {}", e),
+            StoreError::Corrupt(key) => write!(f, "Record `{}` is corrupted", key),
+        }
+    }
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Opaque values kept next to the articles, by what they are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Blobs {
+    /// Title embeddings, keyed by file name
+    Embeddings,
+    /// Cluster snapshots, keyed by language code
+    Threads,
+    /// Thread timelines, keyed by thread id
+    Timelines,
+    /// Per host statistics, keyed by host
+    Sources,
+}
+
+impl Blobs {
+    pub fn name(self) -> &'static str {
+        match self {
+            Blobs::Embeddings => "embeddings",
+            Blobs::Threads => "threads",
+            Blobs::Timelines => "timelines",
+            Blobs::Sources => "sources",
+        }
+    }
+}
+
+pub trait ArticleStore: Send + Sync {
+    /// Store an article under its `file_name`, replacing any previous version
+    fn put(&self, article: &ProtoFile) -> StoreResult<()>;
+    /// Store an article only if the stored version still has the content hash `current`(`None` if
+    /// there shouldn't be one), checking and writing atomically
+    ///
+    /// Returns whether it was stored
+    fn put_if(&self, article: &ProtoFile, current: Option<&str>) -> StoreResult<bool>;
+    fn get(&self, file: &str) -> StoreResult<Option<ProtoFile>>;
+    fn contains(&self, file: &str) -> StoreResult<bool>;
+    /// Remove an article, returning it if it was there
+    fn delete(&self, file: &str) -> StoreResult<Option<ProtoFile>>;
+    /// Visit every article
+    ///
+    /// Records that can't be read are passed as `StoreError::Corrupt`. `visit` may change the store,
+    /// whether it then sees the change is up to the backend
+    fn scan(&self, visit: &mut dyn FnMut(StoreResult<ProtoFile>)) -> StoreResult<()>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The time to live, i.e the publish time of the newest article, `None` if nothing was stored yet
+    fn ttl(&self) -> Option<u64>;
+    /// Move the time to live forward to `time`, it never moves back
+    fn update_ttl(&self, time: u64);
+    /// Bumped on every change to the articles, see `snapshot.rs`
+    fn generation(&self) -> u64;
+    fn bump_generation(&self);
+    fn get_blob(&self, blobs: Blobs, key: &str) -> StoreResult<Option<Vec<u8>>>;
+    fn put_blob(&self, blobs: Blobs, key: &str, value: &[u8]) -> StoreResult<()>;
+    fn delete_blob(&self, blobs: Blobs, key: &str) -> StoreResult<()>;
+    fn blob_keys(&self, blobs: Blobs) -> StoreResult<Vec<String>>;
+    /// Make sure everything written so far survives a crash
+    fn flush(&self) -> StoreResult<()>;
+    /// Bytes used on disk, `None` where that doesn't make sense
+    fn size_on_disk(&self) -> Option<u64>;
+}
+
+/// Open the backend the configuration asks for
+pub fn open(config: &StoreConfig) -> Box<dyn ArticleStore> {
+    let store: Box<dyn ArticleStore> = match config.backend {
+        Backend::Sled => Box::new(SledStore::open()),
+        Backend::Memory => Box::new(MemoryStore::default()),
+        Backend::Sqlite => Box::new(SqliteStore::open(config.sqlite_path.as_str())),
+    };
+    info!(backend = config.backend.name(), articles = store.len(); "Opened article store");
+    store
+}
+
+lazy_static! {
+    /// The store everything uses, opened on first use from Rocket's configuration
+    static ref STORE: Box<dyn ArticleStore> =
+        open(&ServerConfig::from_figment(&Config::figment()).store);
+}
+
+/// The article store
+pub fn store() -> &'static dyn ArticleStore {
+    STORE.as_ref()
+}
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use crate::server::protos::server_files::ProtoFile;
+use crate::server::store::{ArticleStore, Blobs, StoreResult};
+
+/// Keeps everything in memory, gone when the process exits
+#[derive(Default)]
+pub struct MemoryStore {
+    articles: RwLock<BTreeMap<String, ProtoFile>>,
+    ttl: RwLock<Option<u64>>,
+    generation: RwLock<u64>,
+    blobs: RwLock<HashMap<(Blobs, String), Vec<u8>>>,
+}
+
+impl ArticleStore for MemoryStore {
+    fn put(&self, article: &ProtoFile) -> StoreResult<()> {
+        self.articles
+            .write()
+            .unwrap()
+            .insert(article.file_name.clone(), article.clone());
+        Ok(())
+    }
+    fn put_if(&self, article: &ProtoFile, current: Option<&str>) -> StoreResult<bool> {
+        let mut articles = self.articles.write().unwrap();
+        let stored = articles
+            .get(&article.file_name)
+            .map(|file| file.content_hash.as_str());
+        if stored != current {
+            return Ok(false);
+        }
+        articles.insert(article.file_name.clone(), article.clone());
+        Ok(true)
+    }
+    fn get(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        Ok(self.articles.read().unwrap().get(file).cloned())
+    }
+    fn contains(&self, file: &str) -> StoreResult<bool> {
+        Ok(self.articles.read().unwrap().contains_key(file))
+    }
+    fn delete(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        Ok(self.articles.write().unwrap().remove(file))
+    }
+    fn scan(&self, visit: &mut dyn FnMut(StoreResult<ProtoFile>)) -> StoreResult<()> {
+        // copy them out so `visit` can change the store without deadlocking
+        let articles: Vec<ProtoFile> = self.articles.read().unwrap().values().cloned().collect();
+        articles.into_iter().for_each(|article| visit(Ok(article)));
+        Ok(())
+    }
+    fn len(&self) -> usize {
+        self.articles.read().unwrap().len()
+    }
+    fn ttl(&self) -> Option<u64> {
+        *self.ttl.read().unwrap()
+    }
+    fn update_ttl(&self, time: u64) {
+        let mut ttl = self.ttl.write().unwrap();
+        *ttl = Some(ttl.map_or(time, |old| old.max(time)));
+    }
+    fn generation(&self) -> u64 {
+        *self.generation.read().unwrap()
+    }
+    fn bump_generation(&self) {
+        *self.generation.write().unwrap() += 1;
+    }
+    fn get_blob(&self, blobs: Blobs, key: &str) -> StoreResult<Option<Vec<u8>>> {
+        Ok(self
+            .blobs
+            .read()
+            .unwrap()
+            .get(&(blobs, key.to_string()))
+            .cloned())
+    }
+    fn put_blob(&self, blobs: Blobs, key: &str, value: &[u8]) -> StoreResult<()> {
+        self.blobs
+            .write()
+            .unwrap()
+            .insert((blobs, key.to_string()), value.to_vec());
+        Ok(())
+    }
+    fn delete_blob(&self, blobs: Blobs, key: &str) -> StoreResult<()> {
+        self.blobs
+            .write()
+            .unwrap()
+            .remove(&(blobs, key.to_string()));
+        Ok(())
+    }
+    fn blob_keys(&self, blobs: Blobs) -> StoreResult<Vec<String>> {
+        Ok(self
+            .blobs
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|(b, _)| *b == blobs)
+            .map(|(_, key)| key.clone())
+            .collect())
+    }
+    fn flush(&self) -> StoreResult<()> {
+        Ok(())
+    }
+    fn size_on_disk(&self) -> Option<u64> {
+        None
+    }
+}
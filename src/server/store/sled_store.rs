@@ -0,0 +1,164 @@
+use std::convert::TryInto;
+
+use protobuf::{parse_from_bytes, Message};
+use sled::Tree;
+
+use crate::server::protos::server_files::ProtoFile;
+use crate::server::schema::{
+    self, ARTICLES, EMBEDDINGS, GENERATION_KEY, META, SOURCES, THREADS, TIMELINES, TTL_KEY,
+};
+use crate::server::store::{ArticleStore, Blobs, StoreError, StoreResult};
+use crate::server::GLOBAL_DBASE;
+
+/// The sled backed store, the layout is described in `schema.rs`
+pub struct SledStore;
+
+impl SledStore {
+    /// # Panics
+    /// If the DataBase has another schema version, see `schema::check`
+    pub fn open() -> SledStore {
+        schema::check();
+        SledStore
+    }
+    fn tree(blobs: Blobs) -> &'static Tree {
+        match blobs {
+            Blobs::Embeddings => &*EMBEDDINGS,
+            Blobs::Threads => &*THREADS,
+            Blobs::Timelines => &*TIMELINES,
+            Blobs::Sources => &*SOURCES,
+        }
+    }
+}
+
+fn backend(e: sled::Error) -> StoreError {
+    StoreError::Backend(e.to_string())
+}
+
+fn parse(key: &[u8], value: &[u8]) -> StoreResult<ProtoFile> {
+    parse_from_bytes(value)
+        .map_err(|_| StoreError::Corrupt(String::from_utf8_lossy(key).to_string()))
+}
+
+/// Increment a big endian `u64`, treating a missing value as 0
+fn increment(old: Option<&[u8]>) -> Option<Vec<u8>> {
+    let old = old
+        .and_then(|value| value.try_into().ok())
+        .map_or(0, u64::from_be_bytes);
+    Some((old + 1).to_be_bytes().to_vec())
+}
+
+impl ArticleStore for SledStore {
+    fn put(&self, article: &ProtoFile) -> StoreResult<()> {
+        let bytes = article
+            .write_to_bytes()
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        ARTICLES
+            .insert(article.file_name.as_bytes(), bytes)
+            .map(|_| ())
+            .map_err(backend)
+    }
+    fn put_if(&self, article: &ProtoFile, current: Option<&str>) -> StoreResult<bool> {
+        let bytes = article
+            .write_to_bytes()
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        let key = article.file_name.as_bytes();
+        loop {
+            let old = ARTICLES.get(key).map_err(backend)?;
+            let stored = old
+                .as_ref()
+                .map(|value| parse(key, value.as_ref()))
+                .transpose()?;
+            if stored.as_ref().map(|file| file.content_hash.as_str()) != current {
+                return Ok(false);
+            }
+            let swapped = ARTICLES
+                .compare_and_swap(key, old, Some(bytes.as_slice()))
+                .map_err(backend)?;
+            // otherwise it changed since we read it, check it again
+            if swapped.is_ok() {
+                return Ok(true);
+            }
+        }
+    }
+    fn get(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        match ARTICLES.get(file.as_bytes()).map_err(backend)? {
+            Some(value) => parse(file.as_bytes(), value.as_ref()).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn contains(&self, file: &str) -> StoreResult<bool> {
+        ARTICLES.contains_key(file.as_bytes()).map_err(backend)
+    }
+    fn delete(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        match ARTICLES.remove(file.as_bytes()).map_err(backend)? {
+            Some(value) => parse(file.as_bytes(), value.as_ref()).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn scan(&self, visit: &mut dyn FnMut(StoreResult<ProtoFile>)) -> StoreResult<()> {
+        for entry in ARTICLES.iter() {
+            let (key, value) = entry.map_err(backend)?;
+            visit(parse(key.as_ref(), value.as_ref()));
+        }
+        Ok(())
+    }
+    fn len(&self) -> usize {
+        ARTICLES.len()
+    }
+    fn ttl(&self) -> Option<u64> {
+        schema::read_u64(TTL_KEY)
+    }
+    fn update_ttl(&self, time: u64) {
+        let result = META.update_and_fetch(TTL_KEY, |old| {
+            let old = old
+                .and_then(|value| value.try_into().ok())
+                .map_or(0, u64::from_be_bytes);
+            Some(old.max(time).to_be_bytes().to_vec())
+        });
+        if let Err(e) = result {
+            error!("Could not update time to live\n{}", e);
+        }
+    }
+    fn generation(&self) -> u64 {
+        schema::read_u64(GENERATION_KEY).unwrap_or(0)
+    }
+    fn bump_generation(&self) {
+        if let Err(e) = META.update_and_fetch(GENERATION_KEY, increment) {
+            error!("Could not bump store generation\n{}", e);
+        }
+    }
+    fn get_blob(&self, blobs: Blobs, key: &str) -> StoreResult<Option<Vec<u8>>> {
+        SledStore::tree(blobs)
+            .get(key.as_bytes())
+            .map(|value| value.map(|v| v.to_vec()))
+            .map_err(backend)
+    }
+    fn put_blob(&self, blobs: Blobs, key: &str, value: &[u8]) -> StoreResult<()> {
+        SledStore::tree(blobs)
+            .insert(key.as_bytes(), value)
+            .map(|_| ())
+            .map_err(backend)
+    }
+    fn delete_blob(&self, blobs: Blobs, key: &str) -> StoreResult<()> {
+        SledStore::tree(blobs)
+            .remove(key.as_bytes())
+            .map(|_| ())
+            .map_err(backend)
+    }
+    fn blob_keys(&self, blobs: Blobs) -> StoreResult<Vec<String>> {
+        SledStore::tree(blobs)
+            .iter()
+            .keys()
+            .map(|key| {
+                key.map(|key| String::from_utf8_lossy(key.as_ref()).to_string())
+                    .map_err(backend)
+            })
+            .collect()
+    }
+    fn flush(&self) -> StoreResult<()> {
+        GLOBAL_DBASE.flush().map(|_| ()).map_err(backend)
+    }
+    fn size_on_disk(&self) -> Option<u64> {
+        GLOBAL_DBASE.size_on_disk().ok()
+    }
+}
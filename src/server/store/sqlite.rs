@@ -0,0 +1,269 @@
+use std::sync::Mutex;
+
+use protobuf::{parse_from_bytes, Message};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::server::lang_code;
+use crate::server::enums::HTMLData;
+use crate::server::protos::server_files::ProtoFile;
+use crate::server::store::{ArticleStore, Blobs, StoreError, StoreResult};
+
+/// Key of the time to live in the `meta` table
+const TTL: &str = "ttl";
+/// Key of the store generation in the `meta` table
+const GENERATION: &str = "generation";
+
+/// Articles in an SQLite file
+///
+/// The `articles` table keeps the whole proto in `proto`, the other columns are copies of its fields for
+/// querying, e.g
+/// ```sql
+/// SELECT category, COUNT(*) FROM articles WHERE language = 'en' GROUP BY category;
+/// ```
+pub struct SqliteStore {
+    path: String,
+    connection: Mutex<Connection>,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS articles (
+    file_name TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    language TEXT NOT NULL,
+    category TEXT NOT NULL,
+    date_published INTEGER NOT NULL,
+    proto BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS articles_date ON articles (date_published);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS blobs (
+    tree TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value BLOB NOT NULL,
+    PRIMARY KEY (tree, key)
+);
+";
+
+fn backend(e: rusqlite::Error) -> StoreError {
+    StoreError::Backend(e.to_string())
+}
+
+fn parse(file: &str, proto: &[u8]) -> StoreResult<ProtoFile> {
+    parse_from_bytes(proto).map_err(|_| StoreError::Corrupt(file.to_string()))
+}
+
+impl SqliteStore {
+    /// Open(or create) the database at `path`
+    ///
+    /// # Panics
+    /// If the file can't be opened or the tables can't be created
+    pub fn open(path: &str) -> SqliteStore {
+        let connection = Connection::open(path)
+            .unwrap_or_else(|e| panic!("Could not open SQLite store `{}`\n{}", path, e));
+        connection
+            .execute_batch(SCHEMA)
+            .unwrap_or_else(|e| panic!("Could not create SQLite tables in `{}`\n{}", path, e));
+        SqliteStore {
+            path: path.to_string(),
+            connection: Mutex::new(connection),
+        }
+    }
+    fn meta(&self, key: &str) -> Option<u64> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT value FROM meta WHERE key = ?1", params![key], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+            .unwrap_or_else(|e| {
+                error!("Could not read `{}` from meta\n{}", key, e);
+                None
+            })
+            .map(|value| value as u64)
+    }
+}
+
+/// Insert or replace an article, on a connection that's already locked
+fn insert(connection: &Connection, article: &ProtoFile) -> StoreResult<()> {
+    let proto = article
+        .write_to_bytes()
+        .map_err(|e| StoreError::Backend(e.to_string()))?;
+    let html = HTMLData::from_proto(article.clone());
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO articles
+             (file_name, title, url, language, category, date_published, proto)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                article.file_name,
+                article.title,
+                article.url,
+                html.lang.map_or("en", lang_code),
+                html.category.to_string(),
+                article.date_published,
+                proto
+            ],
+        )
+        .map(|_| ())
+        .map_err(backend)
+}
+
+/// Read an article, on a connection that's already locked
+fn select(connection: &Connection, file: &str) -> StoreResult<Option<ProtoFile>> {
+    let proto: Option<Vec<u8>> = connection
+        .query_row(
+            "SELECT proto FROM articles WHERE file_name = ?1",
+            params![file],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(backend)?;
+    proto.map(|proto| parse(file, &proto)).transpose()
+}
+
+impl ArticleStore for SqliteStore {
+    fn put(&self, article: &ProtoFile) -> StoreResult<()> {
+        insert(&self.connection.lock().unwrap(), article)
+    }
+    fn put_if(&self, article: &ProtoFile, current: Option<&str>) -> StoreResult<bool> {
+        // holding the lock makes the check and the write atomic, everything goes through it
+        let connection = self.connection.lock().unwrap();
+        let stored = select(&connection, article.file_name.as_str())?;
+        if stored.as_ref().map(|file| file.content_hash.as_str()) != current {
+            return Ok(false);
+        }
+        insert(&connection, article).map(|_| true)
+    }
+    fn get(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        select(&self.connection.lock().unwrap(), file)
+    }
+    fn contains(&self, file: &str) -> StoreResult<bool> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM articles WHERE file_name = ?1)",
+                params![file],
+                |row| row.get(0),
+            )
+            .map_err(backend)
+    }
+    fn delete(&self, file: &str) -> StoreResult<Option<ProtoFile>> {
+        let article = self.get(file)?;
+        if article.is_some() {
+            self.connection
+                .lock()
+                .unwrap()
+                .execute("DELETE FROM articles WHERE file_name = ?1", params![file])
+                .map_err(backend)?;
+        }
+        Ok(article)
+    }
+    fn scan(&self, visit: &mut dyn FnMut(StoreResult<ProtoFile>)) -> StoreResult<()> {
+        // read them all first so `visit` can use the connection
+        let rows: Vec<(String, Vec<u8>)> = {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare("SELECT file_name, proto FROM articles")
+                .map_err(backend)?;
+            let rows = statement
+                .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(backend)?;
+            rows.collect::<Result<_, _>>().map_err(backend)?
+        };
+        rows.into_iter()
+            .for_each(|(file, proto)| visit(parse(file.as_str(), &proto)));
+        Ok(())
+    }
+    fn len(&self) -> usize {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM articles", params![], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_or(0, |count| count as usize)
+    }
+    fn ttl(&self) -> Option<u64> {
+        self.meta(TTL)
+    }
+    fn update_ttl(&self, time: u64) {
+        let result = self.connection.lock().unwrap().execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = MAX(value, excluded.value)",
+            params![TTL, time as i64],
+        );
+        if let Err(e) = result {
+            error!("Could not update time to live\n{}", e);
+        }
+    }
+    fn generation(&self) -> u64 {
+        self.meta(GENERATION).unwrap_or(0)
+    }
+    fn bump_generation(&self) {
+        let result = self.connection.lock().unwrap().execute(
+            "INSERT INTO meta (key, value) VALUES (?1, 1)
+             ON CONFLICT(key) DO UPDATE SET value = value + 1",
+            params![GENERATION],
+        );
+        if let Err(e) = result {
+            error!("Could not bump store generation\n{}", e);
+        }
+    }
+    fn get_blob(&self, blobs: Blobs, key: &str) -> StoreResult<Option<Vec<u8>>> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT value FROM blobs WHERE tree = ?1 AND key = ?2",
+                params![blobs.name(), key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(backend)
+    }
+    fn put_blob(&self, blobs: Blobs, key: &str, value: &[u8]) -> StoreResult<()> {
+        self.connection
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO blobs (tree, key, value) VALUES (?1, ?2, ?3)",
+                params![blobs.name(), key, value],
+            )
+            .map(|_| ())
+            .map_err(backend)
+    }
+    fn delete_blob(&self, blobs: Blobs, key: &str) -> StoreResult<()> {
+        self.connection
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM blobs WHERE tree = ?1 AND key = ?2",
+                params![blobs.name(), key],
+            )
+            .map(|_| ())
+            .map_err(backend)
+    }
+    fn blob_keys(&self, blobs: Blobs) -> StoreResult<Vec<String>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT key FROM blobs WHERE tree = ?1")
+            .map_err(backend)?;
+        let keys = statement
+            .query_map(params![blobs.name()], |row| row.get(0))
+            .map_err(backend)?;
+        keys.collect::<Result<_, _>>().map_err(backend)
+    }
+    fn flush(&self) -> StoreResult<()> {
+        // every statement is its own transaction, so it's on disk already
+        Ok(())
+    }
+    fn size_on_disk(&self) -> Option<u64> {
+        std::fs::metadata(self.path.as_str()).ok().map(|m| m.len())
+    }
+}
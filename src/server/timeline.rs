@@ -0,0 +1,241 @@
+//! How each thread evolved
+//!
+//! After every clustering we record, per thread id(see `history.rs`), when the thread was first seen,
+//! when each article joined it, its peak size and when it expired. Timelines are stored next to the
+//! articles(blob `timelines`) so they survive restarts. A thread whose id is missing from a clustering has
+//! expired, the `prune_timelines` job drops its timeline a week later
+//!
+//! `GET /threads/<id>/timeline` returns one with the articles bucketed by hour
+use std::collections::{BTreeMap, HashSet};
+use std::time::UNIX_EPOCH;
+
+use protobuf::{parse_from_bytes, Message};
+use rocket::http::Status;
+use serde::Serialize;
+use whatlang::Lang;
+
+use crate::server::cluster::AllArticles;
+use crate::server::protos::server_files::{Arrival, Timeline};
+use crate::server::store::{store, Blobs};
+use crate::server::top::PrettyJson;
+use crate::server::lang_code;
+
+/// How long a timeline is kept after its thread expired, in seconds
+const RETENTION: u64 = 7 * 24 * 60 * 60;
+const HOUR: u64 = 60 * 60;
+
+fn now() -> u64 {
+    UNIX_EPOCH.elapsed().map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn load(id: &str) -> Option<Timeline> {
+    let value = store().get_blob(Blobs::Timelines, id).ok()??;
+    match parse_from_bytes(&value) {
+        Ok(timeline) => Some(timeline),
+        Err(e) => {
+            warn!(thread = id; "Ignoring unreadable timeline\n{}", e);
+            None
+        }
+    }
+}
+
+pub fn save(timeline: &Timeline) {
+    let result = timeline
+        .write_to_bytes()
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| {
+            store()
+                .put_blob(Blobs::Timelines, timeline.id.as_str(), &bytes)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        error!(thread = timeline.id.as_str(); "Could not store timeline\n{}", e);
+    }
+}
+
+/// Record the articles that joined the threads of a clustering of `lang`, and expire the threads that
+/// aren't in it any more
+pub fn update(lang: Lang, threads: &[AllArticles]) {
+    let now = now();
+    expire_missing(lang, threads, now);
+    for thread in threads.iter().filter(|thread| !thread.id.is_empty()) {
+        let mut changed = false;
+        let mut timeline = load(thread.id.as_str()).unwrap_or_else(|| {
+            changed = true;
+            let mut timeline = Timeline::new();
+            timeline.set_id(thread.id.clone());
+            timeline.set_first_seen(now);
+            timeline
+        });
+        let known: HashSet<String> = timeline
+            .arrivals
+            .iter()
+            .map(|arrival| arrival.article.clone())
+            .collect();
+        for (article, &published) in thread.articles.iter().zip(thread.times.iter()) {
+            if known.contains(article) {
+                continue;
+            }
+            let mut arrival = Arrival::new();
+            arrival.set_article(article.clone());
+            arrival.set_joined(now);
+            arrival.set_published(published as i64);
+            timeline.mut_arrivals().push(arrival);
+            timeline.set_last_updated(now);
+            changed = true;
+        }
+        if timeline.peak_size < thread.articles.len() as u32 {
+            timeline.set_peak_size(thread.articles.len() as u32);
+            changed = true;
+        }
+        // a thread whose articles came back(e.g an article was uploaded again)
+        if timeline.expired != 0 {
+            timeline.set_expired(0);
+            changed = true;
+        }
+        if changed {
+            save(&timeline);
+        }
+    }
+}
+
+/// Record that a thread expired
+pub fn expire(id: &str) {
+    if let Some(mut timeline) = load(id) {
+        if timeline.expired == 0 {
+            timeline.set_expired(now());
+            save(&timeline);
+        }
+    }
+}
+
+/// Expire the live timelines of `lang` whose thread isn't in `threads`
+fn expire_missing(lang: Lang, threads: &[AllArticles], now: u64) {
+    let keys = match store().blob_keys(Blobs::Timelines) {
+        Ok(keys) => keys,
+        Err(e) => {
+            error!("Could not list timelines\n{}", e);
+            return;
+        }
+    };
+    // thread ids start with their language
+    let prefix = format!("{}-", lang_code(lang));
+    let current: HashSet<&str> = threads.iter().map(|thread| thread.id.as_str()).collect();
+    for key in keys {
+        if !key.starts_with(prefix.as_str()) || current.contains(key.as_str()) {
+            continue;
+        }
+        if let Some(mut timeline) = load(key.as_str()) {
+            if timeline.expired == 0 {
+                timeline.set_expired(now);
+                save(&timeline);
+            }
+        }
+    }
+}
+
+/// Every stored timeline, for exports
+pub fn all() -> Vec<Timeline> {
+    match store().blob_keys(Blobs::Timelines) {
+        Ok(keys) => keys.iter().filter_map(|key| load(key.as_str())).collect(),
+        Err(e) => {
+            error!("Could not list timelines\n{}", e);
+            vec![]
+        }
+    }
+}
+
+/// Drop the timelines of threads that expired more than `RETENTION` ago
+pub fn prune() {
+    let keys = match store().blob_keys(Blobs::Timelines) {
+        Ok(keys) => keys,
+        Err(e) => {
+            error!("Could not list timelines\n{}", e);
+            return;
+        }
+    };
+    let cutoff = now().saturating_sub(RETENTION);
+    let mut removed = 0;
+    for key in keys {
+        let expired = load(key.as_str()).map_or(0, |timeline| timeline.expired);
+        if expired != 0 && expired < cutoff {
+            if let Err(e) = store().delete_blob(Blobs::Timelines, key.as_str()) {
+                error!(thread = key.as_str(); "Could not remove timeline\n{}", e);
+            } else {
+                removed += 1;
+            }
+        }
+    }
+    if removed > 0 {
+        debug!(removed = removed; "Pruned timelines");
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct Bucket {
+    /// Start of the hour, unix seconds
+    pub hour: u64,
+    /// Articles published in this hour
+    pub published: usize,
+    /// Articles that joined the thread in this hour
+    pub joined: usize,
+}
+
+#[derive(Serialize)]
+pub struct TimelineReport {
+    pub id: String,
+    pub first_seen: u64,
+    pub last_updated: u64,
+    /// `None` while the thread is alive
+    pub expired: Option<u64>,
+    pub peak_size: u32,
+    /// Articles that were ever in the thread
+    pub articles: usize,
+    /// Oldest first, hours without articles are left out
+    pub hours: Vec<Bucket>,
+}
+
+impl TimelineReport {
+    fn from_timeline(timeline: Timeline) -> TimelineReport {
+        let mut hours: BTreeMap<u64, Bucket> = BTreeMap::new();
+        for arrival in timeline.arrivals.iter() {
+            let published = arrival.published.max(0) as u64 / HOUR * HOUR;
+            hours
+                .entry(published)
+                .or_insert_with(|| Bucket {
+                    hour: published,
+                    ..Bucket::default()
+                })
+                .published += 1;
+            let joined = arrival.joined / HOUR * HOUR;
+            hours
+                .entry(joined)
+                .or_insert_with(|| Bucket {
+                    hour: joined,
+                    ..Bucket::default()
+                })
+                .joined += 1;
+        }
+        TimelineReport {
+            id: timeline.id,
+            first_seen: timeline.first_seen,
+            last_updated: timeline.last_updated,
+            expired: if timeline.expired == 0 {
+                None
+            } else {
+                Some(timeline.expired)
+            },
+            peak_size: timeline.peak_size,
+            articles: timeline.arrivals.len(),
+            hours: hours.into_iter().map(|(_, bucket)| bucket).collect(),
+        }
+    }
+}
+
+/// The timeline of a thread, also for threads that expired in the last week
+#[get("/threads/<id>/timeline")]
+pub async fn get_timeline(id: String) -> Result<PrettyJson<TimelineReport>, Status> {
+    load(id.as_str())
+        .map(|timeline| PrettyJson(TimelineReport::from_timeline(timeline)))
+        .ok_or(Status::NotFound)
+}
@@ -1,24 +1,25 @@
 #![allow(unused_variables)]
 
-use std::collections::BTreeMap;
-use std::convert::TryInto;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashSet;
+use std::time::UNIX_EPOCH;
 
-use colored::Colorize;
 use rocket::{http, response};
 use rocket::http::Status;
-use rocket::Request;
+use rocket::{Request, State};
 use rocket::response::content;
 use serde::Serialize;
+use whatlang::Lang;
 
-use crate::server::{
-    EN_CLUSTERS, FINISHED_CLUSTERING_EN, FINISHED_CLUSTERING_RU, FINISHED_REBUILDING, GLOBAL_DBASE,
-    RU_CLUSTERS,
-};
+use crate::embeddings::{fallbacks, representation};
+use crate::ranking::{collapse_threads, sort_threads, SortBy};
+use crate::server::{lang_from_code, EN_CLUSTERS, RU_CLUSTERS};
 use crate::server::cluster::AllArticles;
+use crate::server::protos::read_ttl;
+use crate::server::request_id::RequestId;
+use crate::server::state::ServerState;
 use crate::slink::Articles;
 
-pub struct PrettyJson<T>(T);
+pub struct PrettyJson<T>(pub(crate) T);
 
 impl<T> Default for PrettyJson<T>
     where
@@ -31,72 +32,118 @@ impl<T> Default for PrettyJson<T>
 
 impl<'a, T: Serialize> response::Responder<'a, 'static> for PrettyJson<T> {
     fn respond_to(self, request: &'a Request<'_>) -> response::Result<'static> {
-        match request.client_ip() {
-            Some(a) => {
-                eprintln!(
-                    "[PrettyJSON]: Responding to IP : {} for path: {} with query: {}",
-                    a.to_string().red(),
-                    request.uri().path().blue(),
-                    request.uri().query().unwrap_or("stuff").green()
-                );
-            }
-            None => (),
-        }
+        info!(
+            request_id = RequestId::of(request).as_str(),
+            client = request.client_ip().map(|ip| ip.to_string()).unwrap_or_default().as_str(),
+            path = request.uri().path(),
+            query = request.uri().query().unwrap_or("");
+            "Responding with JSON"
+        );
         serde_json::to_string_pretty(&self.0)
             .map(|string| content::Json(string).respond_to(request).unwrap())
             .map_err(|_e| http::Status::InternalServerError)
     }
 }
 
-#[get("/threads?<period>&<lang_code>&category=any")]
+/// A `/threads` response
+#[derive(Serialize)]
+pub struct Threads<T> {
+    /// What the articles were clustered by, see `embeddings.rs`
+    pub representation: &'static str,
+    /// Articles embedded by their title alone, since they had no body
+    pub title_fallbacks: u64,
+    pub threads: Vec<T>,
+}
+
+impl<T> Threads<T> {
+    fn new(threads: Vec<T>) -> Threads<T> {
+        Threads {
+            representation: representation().name(),
+            title_fallbacks: fallbacks(),
+            threads,
+        }
+    }
+}
+
+/// The order asked for with `sort`, by size if left out
+fn sort_by(sort: Option<String>) -> Result<SortBy, Status> {
+    match sort {
+        Some(name) => SortBy::from_name(name.as_str()).ok_or(Status::BadRequest),
+        None => Ok(SortBy::default()),
+    }
+}
+
+/// The articles of a language that are near duplicates of an older article
+fn duplicates(lang: Lang) -> HashSet<String> {
+    match lang {
+        Lang::Rus => RU_CLUSTERS.read().unwrap().duplicates(),
+        _ => EN_CLUSTERS.read().unwrap().duplicates(),
+    }
+}
+
+/// Drop `duplicates` from the threads, and the threads left empty
+fn exclude_duplicates<T>(
+    threads: &mut Vec<T>,
+    duplicates: &HashSet<String>,
+    articles: fn(&T) -> &Vec<String>,
+    remove: fn(&mut T, &str) -> Option<bool>,
+) {
+    for thread in threads.iter_mut() {
+        let found: Vec<String> = articles(thread)
+            .iter()
+            .filter(|article| duplicates.contains(*article))
+            .cloned()
+            .collect();
+        for article in found {
+            remove(thread, article.as_str());
+        }
+    }
+    threads.retain(|thread| !articles(thread).is_empty());
+}
+
+#[get("/threads?<period>&<lang_code>&<sort>&<collapse>&<exclude_duplicates>&category=any")]
 pub async fn get_all_top(
     period: u64,
     lang_code: String,
-) -> Result<PrettyJson<BTreeMap<String, Vec<AllArticles>>>, Status> {
-    unsafe {
-        if !FINISHED_REBUILDING {
-            warn!("Files not rebuilt from database, cannot process requests");
-            return Err(Status::ServiceUnavailable);
-        } else if !FINISHED_CLUSTERING_EN && lang_code.as_str() == "en" {
-            warn!("English clustering not finished, cannot handle en requests now");
-            return Err(Status::ServiceUnavailable);
-        } else if !FINISHED_CLUSTERING_RU && lang_code.as_str() == "ru" {
-            warn!("RU clustering not finished returning service unavailable");
-            return Err(Status::ServiceUnavailable);
-        }
-    }
+    sort: Option<String>,
+    collapse: Option<bool>,
+    exclude_duplicates: Option<bool>,
+    state: State<'_, ServerState>,
+) -> Result<PrettyJson<Threads<AllArticles>>, Status> {
+    let lang = lang_from_code(lang_code.as_str()).ok_or(Status::BadRequest)?;
+    let sort = sort_by(sort)?;
+    state.check_readable(lang)?;
     let mut files = match lang_code.as_str() {
         "en" => EN_CLUSTERS.write().unwrap().get_all(),
         "ru" => RU_CLUSTERS.write().unwrap().get_all(),
         _ => return Err(Status::BadRequest),
     };
     files = remove_more_stale_docs(files.as_slice(), period);
-    files.sort_unstable_by(|a, b| a.articles.len().cmp(&b.articles.len()));
-    files.reverse();
-    let mut tree = BTreeMap::new();
-    tree.insert("threads".to_string(), files);
-    return Ok(PrettyJson(tree));
+    if exclude_duplicates.unwrap_or(false) {
+        let duplicates = duplicates(lang);
+        self::exclude_duplicates(&mut files, &duplicates, |t| &t.articles, AllArticles::remove_file);
+    }
+    sort_threads(&mut files, sort, now());
+    if collapse.unwrap_or(false) {
+        collapse_threads(&mut files);
+    }
+    return Ok(PrettyJson(Threads::new(files)));
 }
 
 #[rustfmt::skip]
-#[get("/threads?<period>&<lang_code>&<category>")]
+#[get("/threads?<period>&<lang_code>&<category>&<sort>&<collapse>&<exclude_duplicates>")]
 pub async fn get_top(
     period: u64,
     lang_code: String,
     category: String,
-) -> Result<PrettyJson<BTreeMap<String, Vec<Articles>>>, Status> {
-    unsafe {
-        if !FINISHED_REBUILDING {
-            warn!("Files not rebuilt from database, cannot process requests");
-            return Err(Status::ServiceUnavailable);
-        } else if !FINISHED_CLUSTERING_EN && lang_code.as_str() == "en" {
-            warn!("English clustering not finished, cannot handle en requests now");
-            return Err(Status::ServiceUnavailable);
-        } else if !FINISHED_CLUSTERING_RU && lang_code.as_str() == "ru" {
-            warn!("RU clustering not finished returning service unavailable");
-            return Err(Status::ServiceUnavailable);
-        }
-    }
+    sort: Option<String>,
+    collapse: Option<bool>,
+    exclude_duplicates: Option<bool>,
+    state: State<'_, ServerState>,
+) -> Result<PrettyJson<Threads<Articles>>, Status> {
+    let lang = lang_from_code(lang_code.as_str()).ok_or(Status::BadRequest)?;
+    let sort = sort_by(sort)?;
+    state.check_readable(lang)?;
     let mut articles = {
         match lang_code.as_str() {
             "en" => {
@@ -130,28 +177,27 @@ pub async fn get_top(
         }
     };
     articles = remove_stale_docs(articles.as_slice(), period);
-    // sort by importance
-    articles.sort_unstable_by(|a, b| a.articles.len().cmp(&b.articles.len()));
-    articles.reverse();
-    let mut tree = BTreeMap::new();
-    tree.insert("threads".to_string(), articles);
-    return Ok(PrettyJson(tree));
+    if exclude_duplicates.unwrap_or(false) {
+        let duplicates = duplicates(lang);
+        self::exclude_duplicates(&mut articles, &duplicates, |t| &t.articles, Articles::remove_file);
+    }
+    sort_threads(&mut articles, sort, now());
+    if collapse.unwrap_or(false) {
+        collapse_threads(&mut articles);
+    }
+    return Ok(PrettyJson(Threads::new(articles)));
+}
+
+/// The publish time of the newest article, or the current time if there is none
+fn now() -> u64 {
+    read_ttl().unwrap_or_else(|| UNIX_EPOCH.elapsed().unwrap().as_secs())
 }
 
 /// remove stale documents taking a mutable reference to the articles
 ///
 /// If TTL doesn't exist we use the current system ,meaning we will return nothing
 fn remove_stale_docs(articles: &[Articles], period: u64) -> Vec<Articles> {
-    let ttl = match GLOBAL_DBASE.get(b"TTL") {
-        Ok(ttl) => match ttl {
-            None => SystemTime::from(UNIX_EPOCH).elapsed().unwrap().as_secs(),
-            Some(time) => {
-                let time_to_live: [u8; 8] = time.as_ref().try_into().unwrap();
-                u64::from_be_bytes(time_to_live)
-            }
-        },
-        Err(_) => SystemTime::from(UNIX_EPOCH).elapsed().unwrap().as_secs(),
-    };
+    let ttl = now();
     articles
         .iter()
         .map(|a| a.remove_stale_documents(ttl, period))
@@ -160,16 +206,7 @@ fn remove_stale_docs(articles: &[Articles], period: u64) -> Vec<Articles> {
 }
 
 fn remove_more_stale_docs(articles: &[AllArticles], period: u64) -> Vec<AllArticles> {
-    let ttl = match GLOBAL_DBASE.get(b"TTL") {
-        Ok(ttl) => match ttl {
-            None => SystemTime::from(UNIX_EPOCH).elapsed().unwrap().as_secs(),
-            Some(time) => {
-                let time_to_live: [u8; 8] = time.as_ref().try_into().unwrap();
-                u64::from_be_bytes(time_to_live)
-            }
-        },
-        Err(_) => SystemTime::from(UNIX_EPOCH).elapsed().unwrap().as_secs(),
-    };
+    let ttl = now();
     articles
         .iter()
         .map(|a| a.remove_stale_docs(ttl, period))
@@ -0,0 +1,766 @@
+//! Exporting the article store to a file and importing it back, e.g to seed a staging server
+//!
+//! An export is a header(schema version, time to live and, for unfiltered exports, the thread state,
+//! timelines and source stats)
+//! followed by one record per article, in one of two formats
+//! * `jsonl`: one JSON object per line, readable and easy to edit
+//! * `proto`: length delimited protobuf, an `ExportHeader` then `ProtoFile`s, smaller and faster
+//!
+//! Both directions take the same filters(language, category and publish date), so an import can
+//! pick a part of a larger export
+//!
+//! Run them against a stopped server, sled only lets one process open the DataBase
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+
+use chrono::NaiveDate;
+use protobuf::{CodedInputStream, Message, ProtobufError, RepeatedField};
+use serde::{Deserialize, Serialize};
+use whatlang::Lang;
+
+use crate::categories::enums::Categories;
+use crate::server::protos::server_files::{
+    Arrival, Category, ClusterSnapshot, ExportHeader, Language, ProtoFile, SourceStats, Thread,
+    ThreadShare, Timeline,
+};
+use crate::server::history::HistoryEntry;
+use crate::server::protos::{category_from_proto, category_to_proto};
+use crate::server::schema::{self, SCHEMA_VERSION};
+use crate::server::snapshot;
+use crate::server::source_stats;
+use crate::server::store::{store, Blobs, StoreError};
+use crate::server::timeline;
+use crate::server::lang_code;
+
+#[derive(Debug)]
+pub enum TransferError {
+    Io(io::Error),
+    Store(StoreError),
+    /// The file isn't a valid export, with the line(jsonl) or record(proto) it failed at
+    Format(usize, String),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::Io(e) => write!(f, "IO error: {}", e),
+            TransferError::Store(e) => write!(f, "{}", e),
+            TransferError::Format(record, e) => write!(f, "Invalid record {}: {}", record, e),
+        }
+    }
+}
+
+impl From<io::Error> for TransferError {
+    fn from(e: io::Error) -> Self {
+        TransferError::Io(e)
+    }
+}
+
+impl From<StoreError> for TransferError {
+    fn from(e: StoreError) -> Self {
+        TransferError::Store(e)
+    }
+}
+
+impl From<ProtobufError> for TransferError {
+    fn from(e: ProtobufError) -> Self {
+        match e {
+            ProtobufError::IoError(e) => TransferError::Io(e),
+            e => TransferError::Format(0, e.to_string()),
+        }
+    }
+}
+
+pub type TransferResult<T> = Result<T, TransferError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Jsonl,
+    Proto,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "jsonl" => Some(Format::Jsonl),
+            "proto" => Some(Format::Proto),
+            _ => None,
+        }
+    }
+}
+
+/// Which articles to export or import, every field that is set must match
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub language: Option<Language>,
+    pub category: Option<Category>,
+    /// Published at or after, in seconds
+    pub since: Option<i64>,
+    /// Published before, in seconds
+    pub until: Option<i64>,
+}
+
+impl Filter {
+    /// Build a filter from the command line values
+    ///
+    /// Dates are either unix seconds or `YYYY-MM-DD`(UTC), a day given to `until` is included
+    pub fn parse(
+        lang: Option<&str>,
+        category: Option<&str>,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Filter, String> {
+        let language = lang.map(language_from_code).transpose()?;
+        let category = category.map(category_from_name).transpose()?;
+        Ok(Filter {
+            language,
+            category,
+            since: since.map(|date| parse_date(date, false)).transpose()?,
+            until: until.map(|date| parse_date(date, true)).transpose()?,
+        })
+    }
+    pub fn is_empty(&self) -> bool {
+        self.language.is_none()
+            && self.category.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+    }
+    pub fn matches(&self, article: &ProtoFile) -> bool {
+        self.language.map_or(true, |l| article.language == l)
+            && self.category.map_or(true, |c| article.category == c)
+            && self.since.map_or(true, |t| article.date_published >= t)
+            && self.until.map_or(true, |t| article.date_published < t)
+    }
+}
+
+/// Unix seconds or a day, `end_of_day` moves a day to the start of the next one
+fn parse_date(date: &str, end_of_day: bool) -> Result<i64, String> {
+    if let Ok(seconds) = date.parse::<i64>() {
+        return Ok(if end_of_day { seconds + 1 } else { seconds });
+    }
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date `{}`, expected unix seconds or YYYY-MM-DD", date))?;
+    let day = if end_of_day { day.succ() } else { day };
+    Ok(day.and_hms(0, 0, 0).timestamp())
+}
+
+/// What an export or import did
+#[derive(Serialize, Default)]
+pub struct TransferReport {
+    /// Articles written to the file or the store
+    pub articles: usize,
+    /// Articles the filter left out
+    pub filtered: usize,
+    /// Records that couldn't be read, by key on export and by record number on import
+    pub unreadable: Vec<String>,
+    /// Languages whose threads were carried over
+    pub threads: Vec<&'static str>,
+    /// Thread timelines carried over
+    pub timelines: usize,
+    /// Hosts whose stats were carried over
+    pub sources: usize,
+    pub ttl: Option<u64>,
+}
+
+/// An article as a line of a `jsonl` export
+#[derive(Serialize, Deserialize)]
+struct JsonArticle {
+    title: String,
+    file_name: String,
+    url: String,
+    date_published: i64,
+    language: String,
+    category: String,
+    accuracy: f32,
+    gb_rating: f32,
+    ru_rating: f32,
+    us_rating: f32,
+    content_hash: String,
+    max_age: i64,
+    #[serde(default)]
+    simhash: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    duplicate_of: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonThread {
+    title: String,
+    category: String,
+    decay: i64,
+    articles: Vec<String>,
+    times: Vec<i64>,
+    centroid: Vec<f32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<HistoryEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hosts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSnapshot {
+    generation: u64,
+    language: String,
+    all: Vec<JsonThread>,
+    categories: Vec<JsonThread>,
+    articles: u64,
+    #[serde(default)]
+    representation: String,
+    #[serde(default)]
+    window: u64,
+    #[serde(default)]
+    doubling: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonArrival {
+    article: String,
+    joined: u64,
+    published: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTimeline {
+    id: String,
+    first_seen: u64,
+    last_updated: u64,
+    expired: u64,
+    peak_size: u32,
+    arrivals: Vec<JsonArrival>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonThreadShare {
+    thread: String,
+    first: bool,
+    large: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSourceStats {
+    host: String,
+    articles: u64,
+    not_news: u64,
+    threads: u64,
+    first_reports: u64,
+    large_threads: u64,
+    live: Vec<JsonThreadShare>,
+}
+
+/// The first line of a `jsonl` export
+#[derive(Serialize, Deserialize)]
+struct JsonHeader {
+    version: u32,
+    ttl: Option<u64>,
+    threads: Vec<JsonSnapshot>,
+    #[serde(default)]
+    timelines: Vec<JsonTimeline>,
+    #[serde(default)]
+    sources: Vec<JsonSourceStats>,
+}
+
+fn language_code(language: Language) -> &'static str {
+    match language {
+        Language::Eng => "en",
+        Language::Rus => "ru",
+    }
+}
+
+fn language_from_code(code: &str) -> Result<Language, String> {
+    match code {
+        "en" => Ok(Language::Eng),
+        "ru" => Ok(Language::Rus),
+        _ => Err(format!("Unknown language `{}`", code)),
+    }
+}
+
+fn category_from_name(name: &str) -> Result<Category, String> {
+    Categories::from_name(name)
+        .map(category_to_proto)
+        .ok_or_else(|| format!("Unknown category `{}`", name))
+}
+
+impl From<&ProtoFile> for JsonArticle {
+    fn from(file: &ProtoFile) -> Self {
+        JsonArticle {
+            title: file.title.clone(),
+            file_name: file.file_name.clone(),
+            url: file.url.clone(),
+            date_published: file.date_published,
+            language: language_code(file.language).to_string(),
+            category: category_from_proto(file.category).to_string(),
+            accuracy: file.accuracy,
+            gb_rating: file.gb_rating,
+            ru_rating: file.ru_rating,
+            us_rating: file.us_rating,
+            content_hash: file.content_hash.clone(),
+            // lines don't carry their record version, so unknown is always -1
+            max_age: schema::max_age(file).map_or(-1, |_| file.max_age),
+            simhash: file.simhash,
+            duplicate_of: file.duplicate_of.clone(),
+        }
+    }
+}
+
+impl JsonArticle {
+    fn into_proto(self) -> Result<ProtoFile, String> {
+        let mut file = ProtoFile::new();
+        file.set_language(language_from_code(self.language.as_str())?);
+        file.set_category(category_from_name(self.category.as_str())?);
+        file.set_title(self.title);
+        file.set_file_name(self.file_name);
+        file.set_url(self.url);
+        file.set_date_published(self.date_published);
+        file.set_accuracy(self.accuracy);
+        file.set_gb_rating(self.gb_rating);
+        file.set_ru_rating(self.ru_rating);
+        file.set_us_rating(self.us_rating);
+        file.set_content_hash(self.content_hash);
+        file.set_max_age(self.max_age);
+        file.set_simhash(self.simhash);
+        file.set_duplicate_of(self.duplicate_of);
+        Ok(file)
+    }
+}
+
+impl From<&Thread> for JsonThread {
+    fn from(thread: &Thread) -> Self {
+        JsonThread {
+            title: thread.title.clone(),
+            category: category_from_proto(thread.category).to_string(),
+            decay: thread.decay,
+            articles: thread.articles.to_vec(),
+            times: thread.times.clone(),
+            centroid: thread.centroid.clone(),
+            id: thread.id.clone(),
+            history: thread
+                .history
+                .iter()
+                .cloned()
+                .map(HistoryEntry::from_proto)
+                .collect(),
+            hosts: thread.hosts.to_vec(),
+        }
+    }
+}
+
+impl JsonThread {
+    fn into_proto(self) -> Result<Thread, String> {
+        let mut thread = Thread::new();
+        thread.set_category(category_from_name(self.category.as_str())?);
+        thread.set_title(self.title);
+        thread.set_decay(self.decay);
+        thread.set_articles(RepeatedField::from_vec(self.articles));
+        thread.set_times(self.times);
+        thread.set_centroid(self.centroid);
+        thread.set_id(self.id);
+        thread.set_history(self.history.iter().map(HistoryEntry::to_proto).collect());
+        thread.set_hosts(RepeatedField::from_vec(self.hosts));
+        Ok(thread)
+    }
+}
+
+impl From<&ClusterSnapshot> for JsonSnapshot {
+    fn from(snapshot: &ClusterSnapshot) -> Self {
+        JsonSnapshot {
+            generation: snapshot.generation,
+            language: language_code(snapshot.language).to_string(),
+            all: snapshot.all.iter().map(JsonThread::from).collect(),
+            categories: snapshot.categories.iter().map(JsonThread::from).collect(),
+            articles: snapshot.articles,
+            representation: snapshot.representation.clone(),
+            window: snapshot.window,
+            doubling: snapshot.doubling,
+        }
+    }
+}
+
+impl JsonSnapshot {
+    fn into_proto(self) -> Result<ClusterSnapshot, String> {
+        let threads = |threads: Vec<JsonThread>| {
+            threads
+                .into_iter()
+                .map(JsonThread::into_proto)
+                .collect::<Result<Vec<_>, _>>()
+                .map(RepeatedField::from_vec)
+        };
+        let mut snapshot = ClusterSnapshot::new();
+        snapshot.set_generation(self.generation);
+        snapshot.set_language(language_from_code(self.language.as_str())?);
+        snapshot.set_all(threads(self.all)?);
+        snapshot.set_categories(threads(self.categories)?);
+        snapshot.set_articles(self.articles);
+        snapshot.set_representation(self.representation);
+        snapshot.set_window(self.window);
+        snapshot.set_doubling(self.doubling);
+        Ok(snapshot)
+    }
+}
+
+impl From<&Timeline> for JsonTimeline {
+    fn from(timeline: &Timeline) -> Self {
+        JsonTimeline {
+            id: timeline.id.clone(),
+            first_seen: timeline.first_seen,
+            last_updated: timeline.last_updated,
+            expired: timeline.expired,
+            peak_size: timeline.peak_size,
+            arrivals: timeline
+                .arrivals
+                .iter()
+                .map(|arrival| JsonArrival {
+                    article: arrival.article.clone(),
+                    joined: arrival.joined,
+                    published: arrival.published,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonTimeline {
+    fn into_proto(self) -> Timeline {
+        let mut timeline = Timeline::new();
+        timeline.set_id(self.id);
+        timeline.set_first_seen(self.first_seen);
+        timeline.set_last_updated(self.last_updated);
+        timeline.set_expired(self.expired);
+        timeline.set_peak_size(self.peak_size);
+        timeline.set_arrivals(
+            self.arrivals
+                .into_iter()
+                .map(|json| {
+                    let mut arrival = Arrival::new();
+                    arrival.set_article(json.article);
+                    arrival.set_joined(json.joined);
+                    arrival.set_published(json.published);
+                    arrival
+                })
+                .collect(),
+        );
+        timeline
+    }
+}
+
+impl From<&SourceStats> for JsonSourceStats {
+    fn from(stats: &SourceStats) -> Self {
+        JsonSourceStats {
+            host: stats.host.clone(),
+            articles: stats.articles,
+            not_news: stats.not_news,
+            threads: stats.threads,
+            first_reports: stats.first_reports,
+            large_threads: stats.large_threads,
+            live: stats
+                .live
+                .iter()
+                .map(|share| JsonThreadShare {
+                    thread: share.thread.clone(),
+                    first: share.first,
+                    large: share.large,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonSourceStats {
+    fn into_proto(self) -> SourceStats {
+        let mut stats = SourceStats::new();
+        stats.set_host(self.host);
+        stats.set_articles(self.articles);
+        stats.set_not_news(self.not_news);
+        stats.set_threads(self.threads);
+        stats.set_first_reports(self.first_reports);
+        stats.set_large_threads(self.large_threads);
+        stats.set_live(
+            self.live
+                .into_iter()
+                .map(|json| {
+                    let mut share = ThreadShare::new();
+                    share.set_thread(json.thread);
+                    share.set_first(json.first);
+                    share.set_large(json.large);
+                    share
+                })
+                .collect(),
+        );
+        stats
+    }
+}
+
+impl From<&ExportHeader> for JsonHeader {
+    fn from(header: &ExportHeader) -> Self {
+        JsonHeader {
+            version: header.version,
+            ttl: if header.ttl == 0 { None } else { Some(header.ttl) },
+            threads: header.threads.iter().map(JsonSnapshot::from).collect(),
+            timelines: header.timelines.iter().map(JsonTimeline::from).collect(),
+            sources: header.sources.iter().map(JsonSourceStats::from).collect(),
+        }
+    }
+}
+
+impl JsonHeader {
+    fn into_proto(self) -> Result<ExportHeader, String> {
+        let mut header = ExportHeader::new();
+        header.set_version(self.version);
+        header.set_ttl(self.ttl.unwrap_or(0));
+        header.set_threads(
+            self.threads
+                .into_iter()
+                .map(JsonSnapshot::into_proto)
+                .collect::<Result<Vec<_>, _>>()
+                .map(RepeatedField::from_vec)?,
+        );
+        header.set_timelines(
+            self.timelines
+                .into_iter()
+                .map(JsonTimeline::into_proto)
+                .collect(),
+        );
+        header.set_sources(
+            self.sources
+                .into_iter()
+                .map(JsonSourceStats::into_proto)
+                .collect(),
+        );
+        Ok(header)
+    }
+}
+
+/// The saved threads of both languages
+fn stored_threads() -> TransferResult<Vec<ClusterSnapshot>> {
+    let mut threads = vec![];
+    for &lang in [Lang::Eng, Lang::Rus].iter() {
+        if let Some(value) = store().get_blob(Blobs::Threads, lang_code(lang))? {
+            match protobuf::parse_from_bytes(&value) {
+                Ok(snapshot) => threads.push(snapshot),
+                Err(e) => warn!(lang = lang_code(lang); "Not exporting unreadable threads\n{}", e),
+            }
+        }
+    }
+    Ok(threads)
+}
+
+/// Write the articles matching `filter` to `writer`
+///
+/// Thread state, timelines and source stats only make sense with every article, so they're left out of
+/// filtered exports
+pub fn export(writer: &mut dyn Write, format: Format, filter: &Filter) -> TransferResult<TransferReport> {
+    let mut report = TransferReport {
+        ttl: store().ttl(),
+        ..TransferReport::default()
+    };
+    let mut header = ExportHeader::new();
+    header.set_version(SCHEMA_VERSION);
+    header.set_ttl(report.ttl.unwrap_or(0));
+    if filter.is_empty() {
+        header.set_threads(RepeatedField::from_vec(stored_threads()?));
+        report.threads = header.threads.iter().map(|t| language_code(t.language)).collect();
+        header.set_timelines(RepeatedField::from_vec(timeline::all()));
+        report.timelines = header.timelines.len();
+        header.set_sources(RepeatedField::from_vec(source_stats::all()));
+        report.sources = header.sources.len();
+    }
+    match format {
+        Format::Jsonl => {
+            serde_json::to_writer(&mut *writer, &JsonHeader::from(&header))
+                .map_err(io::Error::from)?;
+            writer.write_all(b"\n")?;
+        }
+        Format::Proto => header.write_length_delimited_to_writer(&mut *writer)?,
+    }
+    // the first error writing stops the export, scan can't be interrupted
+    let mut failed: Option<TransferError> = None;
+    store().scan(&mut |result| {
+        if failed.is_some() {
+            return;
+        }
+        let file = match result {
+            Ok(file) => file,
+            Err(StoreError::Corrupt(key)) => {
+                report.unreadable.push(key);
+                return;
+            }
+            Err(e) => {
+                failed = Some(e.into());
+                return;
+            }
+        };
+        if !filter.matches(&file) {
+            report.filtered += 1;
+            return;
+        }
+        let written: TransferResult<()> = match format {
+            Format::Jsonl => serde_json::to_writer(&mut *writer, &JsonArticle::from(&file))
+                .map_err(io::Error::from)
+                .and_then(|_| writer.write_all(b"\n"))
+                .map_err(TransferError::from),
+            Format::Proto => file
+                .write_length_delimited_to_writer(&mut *writer)
+                .map_err(TransferError::from),
+        };
+        match written {
+            Ok(_) => report.articles += 1,
+            Err(e) => failed = Some(e),
+        }
+    })?;
+    if let Some(e) = failed {
+        return Err(e);
+    }
+    writer.flush()?;
+    Ok(report)
+}
+
+/// Read an export, calling `visit` with every article
+fn read(
+    reader: &mut dyn BufRead,
+    format: Format,
+    visit: &mut dyn FnMut(usize, Result<ProtoFile, String>) -> TransferResult<()>,
+) -> TransferResult<ExportHeader> {
+    match format {
+        Format::Jsonl => {
+            let mut lines = reader.lines().enumerate();
+            let header = match lines.next() {
+                Some((_, line)) => serde_json::from_str::<JsonHeader>(line?.as_str())
+                    .map_err(|e| e.to_string())
+                    .and_then(JsonHeader::into_proto)
+                    .map_err(|e| TransferError::Format(1, e))?,
+                None => return Err(TransferError::Format(1, "Missing header".to_string())),
+            };
+            for (number, line) in lines {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // lines don't carry their record version, they're as old as the export
+                let article = serde_json::from_str::<JsonArticle>(line.as_str())
+                    .map_err(|e| e.to_string())
+                    .and_then(JsonArticle::into_proto)
+                    .map(|mut file| {
+                        file.set_version(header.version);
+                        file
+                    });
+                visit(number + 1, article)?;
+            }
+            Ok(header)
+        }
+        Format::Proto => {
+            let mut input = CodedInputStream::from_buffered_reader(reader);
+            let header: ExportHeader = input
+                .read_message()
+                .map_err(|e| TransferError::Format(1, e.to_string()))?;
+            let mut number = 1;
+            while !input.eof()? {
+                number += 1;
+                // a broken record leaves us nowhere to continue from
+                let article: ProtoFile = input
+                    .read_message()
+                    .map_err(|e| TransferError::Format(number, e.to_string()))?;
+                visit(number, Ok(article))?;
+            }
+            Ok(header)
+        }
+    }
+}
+
+/// Load the articles matching `filter` from `reader` into the store, replacing those with the same name
+///
+/// The time to live, the threads, their timelines and the source stats are only taken over by unfiltered
+/// imports into an empty store, otherwise they wouldn't describe what's stored. Articles are embedded and
+/// clustered again on the next server start
+pub fn import(reader: &mut dyn BufRead, format: Format, filter: &Filter) -> TransferResult<TransferReport> {
+    let was_empty = store().is_empty();
+    let mut report = TransferReport::default();
+    let header = read(reader, format, &mut |number, article| {
+        let mut article = match article {
+            Ok(article) => article,
+            Err(e) => {
+                warn!(record = number; "Skipping unreadable record\n{}", e);
+                report.unreadable.push(number.to_string());
+                return Ok(());
+            }
+        };
+        if !filter.matches(&article) {
+            report.filtered += 1;
+            return Ok(());
+        }
+        schema::upgrade(&mut article);
+        store().put(&article)?;
+        store().update_ttl(article.date_published.max(0) as u64);
+        report.articles += 1;
+        Ok(())
+    })?;
+    if header.version > SCHEMA_VERSION {
+        warn!(
+            export = header.version,
+            current = SCHEMA_VERSION;
+            "Export is from a newer schema version, fields it added were dropped"
+        );
+    }
+    snapshot::bump_generation();
+    if was_empty && filter.is_empty() {
+        if header.ttl != 0 {
+            store().update_ttl(header.ttl);
+        }
+        let generation = snapshot::generation();
+        for mut threads in header.threads.into_iter() {
+            let lang = match threads.language {
+                Language::Eng => Lang::Eng,
+                Language::Rus => Lang::Rus,
+            };
+            // it describes exactly what we just stored
+            threads.set_generation(generation);
+            snapshot::save(lang, &threads);
+            report.threads.push(lang_code(lang));
+        }
+        for timeline in header.timelines.iter() {
+            timeline::save(timeline);
+        }
+        report.timelines = header.timelines.len();
+        for stats in header.sources.iter() {
+            source_stats::save(stats);
+        }
+        report.sources = header.sources.len();
+    }
+    store().flush()?;
+    report.ttl = store().ttl();
+    Ok(report)
+}
+
+fn open_output(path: &str) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        "-" => Box::new(BufWriter::new(io::stdout())),
+        path => Box::new(BufWriter::new(File::create(path)?)),
+    })
+}
+
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let reader: Box<dyn Read> = match path {
+        "-" => Box::new(io::stdin()),
+        path => Box::new(File::open(path)?),
+    };
+    Ok(Box::new(BufReader::new(reader)))
+}
+
+/// Entry point for `tgnews export`, `path` may be `-` for stdout. The report goes to stderr
+pub fn export_entry(path: &str, format: Format, filter: &Filter) {
+    let report = open_output(path)
+        .map_err(TransferError::from)
+        .and_then(|mut writer| export(&mut *writer, format, filter))
+        .unwrap_or_else(|e| panic!("Could not export to `{}`\n{}", path, e));
+    eprintln!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+/// Entry point for `tgnews import`, `path` may be `-` for stdin. Prints the report as JSON
+pub fn import_entry(path: &str, format: Format, filter: &Filter) {
+    let report = open_input(path)
+        .map_err(TransferError::from)
+        .and_then(|mut reader| import(&mut *reader, format, filter))
+        .unwrap_or_else(|e| panic!("Could not import `{}`\n{}", path, e));
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
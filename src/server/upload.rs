@@ -1,16 +1,19 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use rocket::data::{ByteUnit, FromData, Outcome};
 use rocket::http::Status;
 
 use crate::news::{server_is_news_en, server_is_news_ru};
-use crate::server::enums::HTMLData;
+use crate::server::enums::{HTErr, HTMLData};
+use crate::server::metrics;
+use crate::server::protos::{read_ttl, to_proto};
+use crate::server::protos::server_files::ProtoFile;
+use crate::server::request_id::RequestId;
+use crate::server::source_stats;
 use crate::server::static_pools::pool;
-use crate::server::{
-    FINISHED_CLUSTERING_EN, FINISHED_CLUSTERING_RU, FINISHED_REBUILDING, GLOBAL_DBASE,
-};
+use crate::server::state::ServerState;
+use crate::server::store::store;
 use rocket::request::FromRequest;
-use rocket::{http, request, Data, Request};
+use rocket::response::Responder;
+use rocket::{http, request, response, Data, Request, Response, State};
 use whatlang::{Detector, Lang};
 
 const LIMIT: ByteUnit = ByteUnit::Megabyte(12);
@@ -24,8 +27,14 @@ impl FromData for HTMLData {
     type Error = HTMLError;
 
     async fn from_data(_: &Request<'_>, data: Data) -> Outcome<Self, Self::Error> {
-        let string = match data.open(LIMIT).stream_to_string().await {
-            Ok(st) => st,
+        let string = match data.open(LIMIT).into_string().await {
+            Ok(st) if !st.is_complete() => {
+                return Outcome::Failure((
+                    Status::PayloadTooLarge,
+                    HTMLError::Format("Article is too large".to_string()),
+                ))
+            }
+            Ok(st) => st.into_inner(),
             Err(e) => {
                 return Outcome::Failure((
                     Status::InternalServerError,
@@ -42,9 +51,27 @@ impl FromData for HTMLData {
         }
     }
 }
-#[allow(dead_code)]
+/// The `max-age` directive of the Cache-Control header
+///
+/// This is how long(in seconds) the article should be kept, counted from the time it was published
 pub struct CacheControl {
-    cache_control: u64,
+    max_age: u64,
+}
+impl CacheControl {
+    /// Parse a Cache-Control header value, e.g `public, max-age=3600`
+    ///
+    /// Returns `None` if there is no `max-age` directive or it isn't a number
+    pub fn parse(header: &str) -> Option<CacheControl> {
+        header
+            .split(',')
+            .map(str::trim)
+            .find_map(|directive| directive.strip_prefix("max-age="))
+            .and_then(|age| age.trim_matches('"').parse::<u64>().ok())
+            .map(|max_age| CacheControl { max_age })
+    }
+    pub fn max_age(&self) -> u64 {
+        self.max_age
+    }
 }
 #[rocket::async_trait]
 impl<'a, 'r> FromRequest<'a, 'r> for CacheControl {
@@ -54,89 +81,324 @@ impl<'a, 'r> FromRequest<'a, 'r> for CacheControl {
     async fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
         const CACHE_CONTROL: &str = "Cache-Control";
         let agent = request.headers().get_one(CACHE_CONTROL);
-        match agent {
-            //If header exists, we convert it to string
-            Some(a) => rocket::outcome::Outcome::Success(CacheControl {
-                cache_control: a
-                    .to_string()
-                    .strip_prefix("max-age=")
-                    .unwrap()
-                    .parse::<u64>()
-                    .unwrap(),
-            }),
-            // If not we send a bad request status code.
-            None => rocket::outcome::Outcome::Failure((http::Status::BadRequest, ())),
+        match agent.and_then(CacheControl::parse) {
+            Some(cache_control) => rocket::outcome::Outcome::Success(cache_control),
+            // If the header is missing or we can't understand it we send a bad request status code.
+            None => {
+                warn!("Missing or malformed Cache-Control header `{:?}`", agent);
+                rocket::outcome::Outcome::Failure((http::Status::BadRequest, ()))
+            }
+        }
+    }
+}
+/// The conditional headers sent with an upload
+///
+/// `If-Match` lets a client only update an article it has seen before,
+/// `If-None-Match: *` lets it only create articles that don't exist yet
+#[derive(Default)]
+pub struct Preconditions {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+}
+impl Preconditions {
+    /// Check the preconditions against the ETag of the article we already have, `None` if we don't
+    /// have one
+    pub fn passes(&self, current: Option<&str>) -> bool {
+        if let Some(tags) = &self.if_match {
+            match current {
+                Some(etag) if etag_list_contains(tags, etag) => {}
+                _ => return false,
+            }
+        }
+        if let (Some(tags), Some(etag)) = (&self.if_none_match, current) {
+            if etag_list_contains(tags, etag) {
+                return false;
+            }
         }
+        true
     }
 }
+/// Check whether a comma separated list of ETags(or `*`) contains `etag`
+fn etag_list_contains(list: &str, etag: &str) -> bool {
+    list.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Preconditions {
+    type Error = ();
+    async fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        rocket::outcome::Outcome::Success(Preconditions {
+            if_match: request.headers().get_one("If-Match").map(String::from),
+            if_none_match: request.headers().get_one("If-None-Match").map(String::from),
+        })
+    }
+}
+/// A status code carrying the ETag of the article it refers to
+pub struct Tagged {
+    status: Status,
+    etag: Option<String>,
+}
+impl From<Status> for Tagged {
+    fn from(status: Status) -> Self {
+        Tagged { status, etag: None }
+    }
+}
+impl<'a> Responder<'a, 'static> for Tagged {
+    fn respond_to(self, _: &'a Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response.status(self.status);
+        if let Some(etag) = self.etag {
+            response.raw_header("ETag", etag);
+        }
+        Ok(response.finalize())
+    }
+}
+/// How an upload went, an article that gets an ETag is classified and handed to `cluster` to be stored
+pub enum Uploaded {
+    /// New article, with its ETag
+    Created(String),
+    /// Replaced an existing article, with its ETag
+    Updated(String),
+    /// Same content as the article we have, with its ETag
+    Unchanged(String),
+    PreconditionFailed,
+    NotNews,
+    WrongLanguage,
+    NoCategory,
+    Expired,
+}
+impl Uploaded {
+    pub fn status(&self) -> Status {
+        match self {
+            Uploaded::Created(_) => Status::Created,
+            Uploaded::PreconditionFailed => Status::PreconditionFailed,
+            _ => Status::NoContent,
+        }
+    }
+    /// The outcome as reported in metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Uploaded::Created(_) => "created",
+            Uploaded::Updated(_) => "updated",
+            Uploaded::Unchanged(_) => "unchanged",
+            Uploaded::PreconditionFailed => "precondition_failed",
+            Uploaded::NotNews => "not_news",
+            Uploaded::WrongLanguage => "wrong_language",
+            Uploaded::NoCategory => "no_category",
+            Uploaded::Expired => "expired",
+        }
+    }
+    pub fn etag(&self) -> Option<&str> {
+        match self {
+            Uploaded::Created(etag) | Uploaded::Updated(etag) | Uploaded::Unchanged(etag) => {
+                Some(etag.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+impl From<Uploaded> for Tagged {
+    fn from(uploaded: Uploaded) -> Self {
+        Tagged {
+            status: uploaded.status(),
+            etag: uploaded.etag().map(String::from),
+        }
+    }
+}
+/// Upload or update an article
+///
+/// Returns `201` when the article is new and `204` when it replaced an existing one,
+/// both with the article's ETag. Articles that aren't stored get `204` without one
 #[put("/<article>", format = "html", data = "<html>")]
 pub async fn upload(
     article: &rocket::http::RawStr,
     html: HTMLData,
-    _cache_control: CacheControl,
-) -> Status {
-    unsafe {
-        if !FINISHED_REBUILDING {
-            return Status::ServiceUnavailable;
-        } else if !FINISHED_CLUSTERING_EN && !FINISHED_CLUSTERING_RU {
-            warn!("Either English Clustering or Russian clustering not finished, sending not implemented");
-            return Status::ServiceUnavailable;
-        }
+    cache_control: CacheControl,
+    preconditions: Preconditions,
+    request_id: RequestId,
+    state: State<'_, ServerState>,
+) -> Tagged {
+    if let Err(status) = state.check_writable() {
+        return status.into();
     }
-    // Check if the store contains the value,if it does skip the rest, am not overwriting values
-    if let Ok(okay) = GLOBAL_DBASE.contains_key(article.as_bytes()) {
-        if okay {
-            warn!("Found existing entry `{}` skipping overwrite", article);
-            return Status::NoContent;
-        }
+    ingest(
+        article.as_str(),
+        html,
+        cache_control.max_age,
+        &preconditions,
+        request_id,
+    )
+    .into()
+}
+/// Check and classify an uploaded article and hand it to `cluster`
+///
+/// This is everything an upload does after parsing the request, shared with `/bulk`.
+/// The article is classified before we reply, so an article without a category never gets an ETag,
+/// and reserved in the store so the preconditions hold against concurrent uploads. It's then
+/// stored in full and clustered in the background, on the global pool
+pub fn ingest(
+    article: &str,
+    html: HTMLData,
+    max_age: u64,
+    preconditions: &Preconditions,
+    request_id: RequestId,
+) -> Uploaded {
+    // Fetch the version we already have, if any
+    let existing: Option<ProtoFile> = store().get(article).ok().flatten();
+    if let Some(uploaded) = settled(
+        existing.as_ref(),
+        &html,
+        preconditions,
+        article,
+        &request_id,
+    ) {
+        return uploaded;
     }
+    let etag = html.etag();
     let lang_info = match Detector::new().detect(html.body.as_str()) {
         // TODO:ADD news filter
         Some(lang) => {
             if (lang.confidence() - 1.0).abs() < f64::EPSILON && lang.lang() == Lang::Eng {
                 // For non-news articles return null
                 if !server_is_news_en(html.title.clone(), html.url.clone()) {
-                    return Status::NoContent;
+                    metrics::upload("not_news");
+                    if existing.is_none() {
+                        source_stats::record_upload(html.url.as_str(), false);
+                    }
+                    return Uploaded::NotNews;
                 }
                 Lang::Eng
             } else if (lang.confidence() - 1.0).abs() < f64::EPSILON && lang.lang() == Lang::Rus {
                 if !server_is_news_ru(html.title.clone(), html.url.clone()) {
-                    return Status::NoContent;
+                    metrics::upload("not_news");
+                    if existing.is_none() {
+                        source_stats::record_upload(html.url.as_str(), false);
+                    }
+                    return Uploaded::NotNews;
                 }
                 Lang::Rus
             } else {
-                return Status::NoContent;
+                metrics::upload("wrong_language");
+                return Uploaded::WrongLanguage;
             }
         }
-        None => return http::Status::NoContent,
+        None => {
+            metrics::upload("wrong_language");
+            return Uploaded::WrongLanguage;
+        }
     };
-    let _time_now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    // TODO: Re-enable this restriction
-    //if time_now-html.date_published > cache_control.cache_control{
-    //  return Status::NoContent
-    //}
     let mut html = html;
+    html.max_age = Some(max_age);
+    // The article may have already expired, relative to the newest article we have
+    if let Some(newest) = read_ttl() {
+        if html.is_expired(newest) {
+            warn!(
+                request_id = request_id.as_str(),
+                article = article,
+                max_age = max_age;
+                "Article has already expired, not storing it"
+            );
+            metrics::upload("expired");
+            return Uploaded::Expired;
+        }
+    }
 
     html.set_lang(lang_info);
     html.set_file_name(article.to_string());
+    if let Err(HTErr::NoCategory(accuracy)) = html.set_category_and_accuracy() {
+        if (accuracy - 1.0).abs() > f32::EPSILON {
+            warn!(
+                request_id = request_id.as_str(),
+                article = article,
+                accuracy = accuracy;
+                "Categories threshold below normal, dropping article"
+            );
+        } else {
+            warn!(
+                request_id = request_id.as_str(),
+                article = article;
+                "Could not determine appropriate category, dropping article"
+            )
+        }
+        metrics::upload("no_category");
+        if existing.is_none() {
+            source_stats::record_upload(html.url.as_str(), false);
+        }
+        return Uploaded::NoCategory;
+    }
+    // Reserve the article before replying, so of two uploads racing for it only the first passes
+    // the preconditions and counts as created. `cluster` fills in the rest of the record
+    let reserved = to_proto(&html);
+    let mut existing = existing;
+    while !store()
+        .put_if(
+            &reserved,
+            existing.as_ref().map(|file| file.content_hash.as_str()),
+        )
+        .expect("Could not add value to DBASE")
+    {
+        // someone else changed it since we looked, check against their version
+        existing = store().get(article).ok().flatten();
+        if let Some(uploaded) = settled(
+            existing.as_ref(),
+            &html,
+            preconditions,
+            article,
+            &request_id,
+        ) {
+            return uploaded;
+        }
+    }
+    metrics::upload(if existing.is_some() { "updated" } else { "created" });
+    if existing.is_none() {
+        source_stats::record_upload(html.url.as_str(), true);
+    }
     // HELLO WORLD
-    pool(crate::server::cluster::cluster(html.clone()));
+    pool(crate::server::cluster::cluster(
+        html,
+        existing.is_some(),
+        request_id,
+    ));
 
-    Status::Created
+    if existing.is_some() {
+        Uploaded::Updated(etag)
+    } else {
+        Uploaded::Created(etag)
+    }
+}
+/// The reply to an upload that doesn't need storing given the version we already have, `None` if it
+/// does
+fn settled(
+    existing: Option<&ProtoFile>,
+    html: &HTMLData,
+    preconditions: &Preconditions,
+    article: &str,
+    request_id: &RequestId,
+) -> Option<Uploaded> {
+    let current_etag = existing.map(|file| format!("\"{}\"", file.content_hash));
+    if !preconditions.passes(current_etag.as_deref()) {
+        metrics::upload("precondition_failed");
+        return Some(Uploaded::PreconditionFailed);
+    }
+    if existing.map_or(false, |file| file.content_hash == html.content_hash) {
+        debug!(
+            request_id = request_id.as_str(),
+            article = article;
+            "Article has not changed, skipping re-processing"
+        );
+        metrics::upload("unchanged");
+        return Some(Uploaded::Unchanged(html.etag()));
+    }
+    None
 }
 #[put("/<_article>", rank = 2)]
-pub async fn malformed_upload(_article: &http::RawStr) -> http::Status {
-    unsafe {
-        if !FINISHED_REBUILDING {
-            warn!("Files not rebuilt from database, cannot process requests");
-            return Status::ServiceUnavailable;
-        } else if !FINISHED_CLUSTERING_RU && !FINISHED_CLUSTERING_EN {
-            warn!("Either English Clustering or Russian clustering not finished, sending not implemented");
-            return Status::ServiceUnavailable;
-        }
+pub async fn malformed_upload(
+    _article: &http::RawStr,
+    state: State<'_, ServerState>,
+) -> http::Status {
+    if let Err(status) = state.check_writable() {
+        return status;
     }
     // If the request doesn't contain necessary information
     return http::Status::UnprocessableEntity;
@@ -1,27 +1,100 @@
 use std::collections::hash_map::Values;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
 
+use crate::categories::enums::Categories;
+use crate::ranking::Rank;
 use crate::server::cluster::SingleArticle;
 use crate::server::enums::HTMLData;
-use crate::server::protos::server_files::ProtoFile;
-use crate::server::GLOBAL_DBASE;
+use crate::server::store::store;
 use crate::threads::annotator::FileAnnotator;
 use eddie::Levenshtein;
 use ndarray::{arr1, Array1, Array2, ArrayView1};
 use ndarray_stats::QuantileExt;
 use num_traits::Float;
-use protobuf::parse_from_bytes;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Default, Clone)]
 pub struct Articles {
+    /// Stable id of the thread on the server, empty otherwise
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub id: String,
     pub title: String,
     #[serde(skip)]
     pub decay: i64,
     #[serde(skip)]
     pub article_times: Vec<u64>,
+    /// Hosts of the articles, in the same order
+    #[serde(skip)]
+    pub hosts: Vec<String>,
+    #[serde(skip)]
+    pub category: Categories,
+    /// Publication time of the oldest article, unix seconds, 0 outside the server
+    #[serde(skip_serializing_if = "is_zero")]
+    pub first_seen: u64,
+    /// Publication time of the newest article
+    #[serde(skip_serializing_if = "is_zero")]
+    pub last_updated: u64,
+    /// Importance score and its parts, set when the threads are ranked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<Rank>,
+    /// Distinct hosts of the articles, set when the threads are ranked
+    #[serde(skip_serializing_if = "is_zero_count")]
+    pub sources: usize,
     pub articles: Vec<String>,
+    /// Articles from the same host as an article in `articles`, by that article. Only when collapsed
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub duplicates: BTreeMap<String, Vec<String>>,
+    /// Near duplicates among `articles` and the article each one copies, see `duplicates.rs`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub duplicate_of: BTreeMap<String, String>,
+}
+
+fn is_zero(time: &u64) -> bool {
+    *time == 0
+}
+
+fn is_zero_count(count: &usize) -> bool {
+    *count == 0
+}
+
+/// The oldest and newest of `times`, `(0, 0)` if there are none
+pub fn time_span(times: &[u64]) -> (u64, u64) {
+    let first = times.iter().min().copied().unwrap_or(0);
+    let last = times.iter().max().copied().unwrap_or(0);
+    (first, last)
+}
+
+/// Keep only the first article of each host, returns the others by the article they were collapsed into
+///
+/// `times` and `hosts` are in the same order as `articles`, articles without a host are always kept
+pub fn collapse_hosts(
+    articles: &mut Vec<String>,
+    times: &mut Vec<u64>,
+    hosts: &mut Vec<String>,
+) -> BTreeMap<String, Vec<String>> {
+    let mut primary: HashMap<String, String> = HashMap::new();
+    let mut duplicates: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let (mut kept, mut kept_times, mut kept_hosts) = (vec![], vec![], vec![]);
+    for (pos, article) in articles.drain(..).enumerate() {
+        let host = hosts.get(pos).cloned().unwrap_or_default();
+        if !host.is_empty() {
+            if let Some(first) = primary.get(&host) {
+                duplicates.entry(first.clone()).or_default().push(article);
+                continue;
+            }
+            primary.insert(host.clone(), article.clone());
+        }
+        if let Some(&time) = times.get(pos) {
+            kept_times.push(time);
+        }
+        kept_hosts.push(host);
+        kept.push(article);
+    }
+    *articles = kept;
+    *times = kept_times;
+    *hosts = kept_hosts;
+    duplicates
 }
 
 impl Articles {
@@ -35,21 +108,38 @@ impl Articles {
         files.reverse();
         let master = files[0].title.clone();
         let mut articles = vec![files[0].file.clone()];
-        files.remove(0);
+        let lead = files.remove(0);
         // sort by lev distance, a shorter edit distance means they are closer
         files.sort_unstable_by_key(|a| lev.distance(master.as_str(), a.title.as_str()));
         articles.extend(files.iter().map(|f| f.file.clone()));
+        let mut article_times = vec![lead.time as u64];
+        article_times.extend(files.iter().map(|f| f.time as u64));
+        let mut hosts = vec![lead.host.clone()];
+        hosts.extend(files.iter().map(|f| f.host.clone()));
         Articles {
+            id: String::new(),
             title: master,
             decay: 0,
-            article_times: vec![],
+            article_times,
+            hosts,
+            category: lead.category,
+            first_seen: 0,
+            last_updated: 0,
+            rank: None,
+            sources: 0,
             articles,
+            duplicates: BTreeMap::new(),
+            duplicate_of: BTreeMap::new(),
         }
     }
     pub fn push(&mut self, file: &SingleArticle) {
         self.title = file.title.to_string();
         self.articles.insert(0, file.file.clone());
         self.article_times.insert(0, file.time);
+        self.hosts.insert(0, file.host.clone());
+        let (first_seen, last_updated) = time_span(&self.article_times);
+        self.first_seen = first_seen;
+        self.last_updated = last_updated;
     }
     /// Create an article from a server file
     pub fn from_server(files: &[SingleArticle]) -> Articles {
@@ -59,6 +149,8 @@ impl Articles {
         let master = files[0].title.clone();
         let mut articles = vec![files[0].file.clone()];
         let mut article_times = vec![files[0].time];
+        let mut hosts = vec![files[0].host.clone()];
+        let category = files[0].category;
         files.remove(0);
         let lev = Levenshtein::new();
         let mut decay = 0;
@@ -66,14 +158,25 @@ impl Articles {
         articles.extend(files.iter().map(|f| {
             decay += f.decay;
             article_times.push(f.time);
+            hosts.push(f.host.clone());
             f.file.clone()
         }));
         decay /= (files.len() + 1).pow(2) as i64;
+        let (first_seen, last_updated) = time_span(&article_times);
         Articles {
+            id: String::new(),
             title: master,
             decay,
             article_times,
+            hosts,
+            category,
+            first_seen,
+            last_updated,
+            rank: None,
+            sources: 0,
             articles,
+            duplicates: BTreeMap::new(),
+            duplicate_of: BTreeMap::new(),
         }
     }
     /// remove stale documents
@@ -92,25 +195,41 @@ impl Articles {
         }
         let mut single_articles = Vec::with_capacity(files.len());
         for i in files {
-            match GLOBAL_DBASE.get(i.as_bytes()) {
-                Ok(value) => match value {
-                    None => {}
-                    Some(finally) => {
-                        let proto: ProtoFile = parse_from_bytes(finally.as_ref()).unwrap();
-                        let html = HTMLData::from_proto(proto);
-                        let file = SingleArticle::from_html(&html);
-                        single_articles.push(file);
-                    }
-                },
-                Err(_) => {}
+            if let Ok(Some(proto)) = store().get(i.as_str()) {
+                let html = HTMLData::from_proto(proto);
+                // the sweeper may not have got to it yet
+                if html.is_expired(ttl_time) {
+                    continue;
+                }
+                let file = SingleArticle::from_html(&html);
+                single_articles.push(file);
             }
         }
         if single_articles.is_empty() {
             return None;
         }
-        let articles = Articles::from_server(single_articles.as_slice());
+        let mut articles = Articles::from_server(single_articles.as_slice());
+        articles.id = self.id.clone();
         return Some(articles);
     }
+    /// Remove a file from this thread
+    ///
+    /// Returns `None` if the file isn't in the thread, otherwise whether it was the lead article
+    /// (the one the thread's title comes from)
+    pub fn remove_file(&mut self, file: &str) -> Option<bool> {
+        let pos = self.articles.iter().position(|a| a == file)?;
+        self.articles.remove(pos);
+        if pos < self.article_times.len() {
+            self.article_times.remove(pos);
+        }
+        if pos < self.hosts.len() {
+            self.hosts.remove(pos);
+        }
+        let (first_seen, last_updated) = time_span(&self.article_times);
+        self.first_seen = first_seen;
+        self.last_updated = last_updated;
+        Some(pos == 0)
+    }
     /// Check whether there is any sign of life in this cluster
     ///
     /// Again a server thing
@@ -134,6 +253,70 @@ where
     a.dot(&b) / ((a.dot(&a) * (b.dot(&b))).sqrt())
 }
 
+/// Dissimilarity of articles published too far apart to be about the same event, above any cutoff
+const FAR_APART: f32 = 2.0;
+
+lazy_static! {
+    static ref TEMPORAL: RwLock<TemporalConfig> = RwLock::new(TemporalConfig::default());
+}
+
+/// How publication time adds to the cosine distance of two articles, so a thread is an event and not
+/// every article on a topic, e.g
+/// ```toml
+/// [default.temporal]
+/// window = 172800
+/// doubling = 86400
+/// ```
+/// Articles without a publication time(0) are compared by their vectors alone
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TemporalConfig {
+    /// Seconds apart after which two articles are never in the same thread, 0 for no limit
+    pub window: u64,
+    /// Seconds apart after which the distance of two articles doubles, 0 to leave it alone
+    pub doubling: u64,
+}
+
+impl Default for TemporalConfig {
+    fn default() -> Self {
+        TemporalConfig {
+            window: 7 * 24 * 60 * 60,
+            doubling: 0,
+        }
+    }
+}
+
+impl TemporalConfig {
+    /// The cosine `distance` of two articles published at `a` and `b`, adjusted for the time between them
+    ///
+    /// Never more than `FAR_APART`, however far apart they are
+    #[allow(clippy::cast_precision_loss)]
+    pub fn dissimilarity(&self, distance: f32, a: u64, b: u64) -> f32 {
+        // doubling nothing stays nothing, and `0 × inf` would be NaN
+        if a == 0 || b == 0 || distance <= 0.0 {
+            return distance;
+        }
+        let apart = if a > b { a - b } else { b - a };
+        if self.window > 0 && apart > self.window {
+            return FAR_APART;
+        }
+        if self.doubling == 0 {
+            return distance;
+        }
+        (distance * 2_f32.powf(apart as f32 / self.doubling as f32)).min(FAR_APART)
+    }
+}
+
+/// Weigh publication time this way from now on
+pub fn configure_temporal(config: TemporalConfig) {
+    *TEMPORAL.write().unwrap() = config;
+}
+
+/// How publication time is weighed
+pub fn temporal() -> TemporalConfig {
+    *TEMPORAL.read().unwrap()
+}
+
 /// Build a dissimilarity matrix of files in the `values` slice
 ///
 /// This runs in `O(n^2)` time
@@ -143,10 +326,14 @@ where
 /// A dissimilarity matrix is a matrix that tells us how similar i and j are by checking the value
 /// at dmin[i,j], we precompute this to prevent us to recompute dissimilarities between  files while clustering them
 ///
+/// The cosine distance is adjusted for how far apart the files were published, see `TemporalConfig`
+///
 /// Overall this is amazing
+#[allow(clippy::cast_sign_loss)]
 fn build_dissimilarity_matrix(values: &[FileAnnotator]) -> (Array2<f32>, Array1<usize>) {
     let mut dissimilarity_matrix = Array2::<f32>::zeros((values.len(), values.len()));
     let mut closest_cluster = Array1::<usize>::ones(values.len());
+    let temporal = temporal();
     for (i, first) in values.iter().enumerate() {
         for (j, second) in values.iter().enumerate() {
             if i == j {
@@ -154,7 +341,8 @@ fn build_dissimilarity_matrix(values: &[FileAnnotator]) -> (Array2<f32>, Array1<
                 dissimilarity_matrix[[i, j]] = 0.0;
             } else {
                 let e = 1.0 - cosine(first.vectors.as_slice(), second.vectors.as_slice());
-                dissimilarity_matrix[[i, j]] = e;
+                dissimilarity_matrix[[i, j]] =
+                    temporal.dissimilarity(e, first.time.max(0) as u64, second.time.max(0) as u64);
             }
             // Store the index with the lowest dissimilarity to X
             // also ensure i is not equal to j
@@ -0,0 +1,345 @@
+//! How much we trust a site, its authority
+//!
+//! Authority comes from the rating files listed in a `SourceConfig`, in one of these formats
+//! * `alexa`, the old Alexa dump, `[{"host": "bbc.co.uk", "rating": 409.0, "country": {"GB": 40.1}}]`,
+//!  authority is `log10(1 + rating)` so about 0 to 2.7
+//! * `scores`, our own editorial scores, `[{"host": "bbc.co.uk", "score": 2.5, "country": {"GB": 40.1}}]`,
+//!  the score is the authority as is and `country` may be left out
+//! * `ranking`, a Tranco style `rank,host` CSV, authority is `log10(1 + 1000 / rank)` so the top site gets 3
+//!  and sites past the first million about 0
+//!
+//! Files are searched in order and the first one rating a host wins, put editorial scores before rankings.
+//! A host is looked up as is and then without its leading labels, `news.bbc.co.uk` gets the rating of
+//! `bbc.co.uk`, down to its registrable domain. That's the label before a public suffix(`public_suffixes` in
+//! the `SourceConfig`, e.g `co.uk` or `blogspot.com`) or a top level domain, so `someone.blogspot.com` never
+//! gets the rating of `blogspot.com`.
+//!
+//! Country scores are the share of a site's audience in a country. Each language we cluster is mapped to a
+//! country, `en` to `US` and `ru` to `RU` unless configured, and articles get the share in their language's
+//! country(see `cluster::cluster`).
+//!
+//! The server reads its `SourceConfig` from `[default.sources]` in `Rocket.toml`, the `threads` and `source`
+//! commands from the JSON file passed with `--sources`. `tgnews source <host>` prints what a host resolves to
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::read_to_string;
+use std::sync::{Arc, RwLock};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::host;
+
+/// Sites ranked better than this get an authority above `log10(2)` in `ranking` files
+const RANKING_TOP: f64 = 1000.0;
+/// Suffixes under which anyone can register a site, the `public_suffixes` of the default `SourceConfig`
+const PUBLIC_SUFFIXES: &[&str] = &[
+    "co.uk",
+    "org.uk",
+    "ac.uk",
+    "gov.uk",
+    "com.au",
+    "net.au",
+    "org.au",
+    "co.jp",
+    "co.in",
+    "co.nz",
+    "co.za",
+    "com.br",
+    "com.cn",
+    "com.tr",
+    "com.ua",
+    "msk.ru",
+    "spb.ru",
+    "narod.ru",
+    "blogspot.com",
+    "github.io",
+    "wordpress.com",
+    "livejournal.com",
+    "tumblr.com",
+    "appspot.com",
+    "herokuapp.com",
+];
+
+lazy_static! {
+    static ref REGISTRY: RwLock<Option<Arc<SourceRegistry>>> = RwLock::new(None);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Alexa,
+    Scores,
+    Ranking,
+}
+
+/// A rating file and its format
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SourceFile {
+    pub path: String,
+    pub format: Format,
+}
+
+/// Where ratings come from, e.g
+/// ```toml
+/// [default.sources]
+/// files = [
+///     { path = "./data/editorial.json", format = "scores" },
+///     { path = "./data/tranco.csv", format = "ranking" },
+/// ]
+/// countries = { en = "GB", ru = "RU" }
+/// public_suffixes = ["co.uk", "blogspot.com", "github.io"]
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SourceConfig {
+    /// Searched in order, the first file rating a host wins
+    pub files: Vec<SourceFile>,
+    /// The country whose audience share counts for articles in a language, by language code
+    pub countries: HashMap<String, String>,
+    /// Suffixes under which anyone can register a site, a host is never rated as one of them
+    pub public_suffixes: Vec<String>,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        let mut countries = HashMap::new();
+        countries.insert("en".to_string(), "US".to_string());
+        countries.insert("ru".to_string(), "RU".to_string());
+        SourceConfig {
+            files: vec![SourceFile {
+                path: "./data/alexa_rating.json".to_string(),
+                format: Format::Alexa,
+            }],
+            countries,
+            public_suffixes: PUBLIC_SUFFIXES.iter().copied().map(String::from).collect(),
+        }
+    }
+}
+
+/// What a rating file says about a host
+#[derive(Clone, Default, Debug)]
+pub struct SourceRating {
+    pub authority: f64,
+    /// Share of the audience in a country, by ISO country code
+    pub country: BTreeMap<String, f64>,
+}
+
+impl SourceRating {
+    /// The authority on the Alexa scale, what `HTMLData::global_rating` always held
+    pub fn global_rating(&self) -> f64 {
+        10_f64.powf(self.authority) - 1.0
+    }
+}
+
+#[derive(Deserialize)]
+struct AlexaEntry {
+    host: String,
+    rating: f32,
+    country: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize)]
+struct ScoreEntry {
+    host: String,
+    score: f64,
+    #[serde(default)]
+    country: BTreeMap<String, f64>,
+}
+
+/// The entries of a JSON rating file
+///
+/// # Panics
+/// If they aren't in its format
+fn parse<T: DeserializeOwned>(file: &SourceFile, contents: &str) -> Vec<T> {
+    serde_json::from_str(contents)
+        .unwrap_or_else(|e| panic!("Invalid source ratings {}\n{}", file.path, e))
+}
+
+/// The ratings of one file, by host
+struct RatingFile {
+    path: String,
+    ratings: HashMap<String, SourceRating>,
+}
+
+impl RatingFile {
+    /// # Panics
+    /// If the file can't be read or isn't in its format
+    fn load(file: &SourceFile) -> RatingFile {
+        let contents = read_to_string(file.path.as_str())
+            .unwrap_or_else(|e| panic!("Could not read source ratings {}\n{}", file.path, e));
+        let ratings: HashMap<String, SourceRating> = match file.format {
+            Format::Alexa => parse::<AlexaEntry>(file, contents.as_str())
+                .into_iter()
+                .map(|entry| {
+                    let rating = SourceRating {
+                        authority: f64::from(entry.rating).ln_1p() / 10_f64.ln(),
+                        country: entry.country,
+                    };
+                    (entry.host, rating)
+                })
+                .collect(),
+            Format::Scores => parse::<ScoreEntry>(file, contents.as_str())
+                .into_iter()
+                .map(|entry| {
+                    let rating = SourceRating {
+                        authority: entry.score,
+                        country: entry.country,
+                    };
+                    (entry.host, rating)
+                })
+                .collect(),
+            Format::Ranking => contents
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.splitn(2, ',');
+                    // headers and blank lines don't parse
+                    let rank: f64 = fields.next()?.trim().parse().ok()?;
+                    let host = fields.next()?.trim().trim_start_matches("www.");
+                    if rank < 1.0 || host.is_empty() {
+                        return None;
+                    }
+                    let rating = SourceRating {
+                        authority: (RANKING_TOP / rank).ln_1p() / 10_f64.ln(),
+                        country: BTreeMap::new(),
+                    };
+                    Some((host.to_string(), rating))
+                })
+                .collect(),
+        };
+        info!(file = file.path.as_str(), hosts = ratings.len(); "Loaded source ratings");
+        RatingFile {
+            path: file.path.clone(),
+            ratings,
+        }
+    }
+}
+
+/// A host's rating and where it came from, what `tgnews source` prints
+#[derive(Serialize)]
+pub struct Resolved {
+    pub host: String,
+    /// The rated host that matched, `host` or one of its parent domains
+    pub matched: String,
+    /// The rating file it's from
+    pub file: String,
+    pub authority: f64,
+    /// Share of the audience in a country, by country code
+    pub country: BTreeMap<String, f64>,
+    /// Share of the audience in each configured language's country, by language code
+    pub languages: BTreeMap<String, f64>,
+}
+
+/// Every rating file, see the module docs
+pub struct SourceRegistry {
+    files: Vec<RatingFile>,
+    countries: HashMap<String, String>,
+    public_suffixes: HashSet<String>,
+}
+
+impl SourceRegistry {
+    /// Load every file in `config`
+    ///
+    /// # Panics
+    /// If a file can't be read or isn't in its format
+    pub fn from_config(config: &SourceConfig) -> SourceRegistry {
+        SourceRegistry {
+            files: config.files.iter().map(RatingFile::load).collect(),
+            countries: config.countries.clone(),
+            public_suffixes: config.public_suffixes.iter().cloned().collect(),
+        }
+    }
+    /// The first rating of `host` or a parent domain, with the host it was found under
+    fn lookup<'a>(&'a self, host: &'a str) -> Option<(&'a str, &'a RatingFile, &'a SourceRating)> {
+        let mut candidate = host;
+        loop {
+            for file in &self.files {
+                if let Some(rating) = file.ratings.get(candidate) {
+                    return Some((candidate, file, rating));
+                }
+            }
+            match candidate.find('.') {
+                // `bbc.co.uk` is as far as `news.bbc.co.uk` goes, its parent `co.uk` is a public suffix
+                Some(dot)
+                    if candidate[dot + 1..].contains('.')
+                        && !self.public_suffixes.contains(&candidate[dot + 1..]) =>
+                {
+                    candidate = &candidate[dot + 1..]
+                }
+                _ => return None,
+            }
+        }
+    }
+    /// The rating of a host, `None` for hosts no file rates
+    pub fn rating(&self, host: &str) -> Option<&SourceRating> {
+        self.lookup(host).map(|(_, _, rating)| rating)
+    }
+    /// How well known a host is, 0 for hosts no file rates
+    pub fn authority(&self, host: &str) -> f64 {
+        self.rating(host).map_or(0.0, |rating| rating.authority)
+    }
+    /// The audience share of a rating in the country configured for `lang`
+    pub fn language_share(&self, rating: &SourceRating, lang: &str) -> Option<f64> {
+        rating.country.get(self.countries.get(lang)?).copied()
+    }
+    /// Everything we know about a host
+    pub fn resolve(&self, host: &str) -> Option<Resolved> {
+        let (matched, file, rating) = self.lookup(host)?;
+        let languages = self
+            .countries
+            .keys()
+            .filter_map(|lang| Some((lang.clone(), self.language_share(rating, lang)?)))
+            .collect();
+        Some(Resolved {
+            host: host.to_string(),
+            matched: matched.to_string(),
+            file: file.path.clone(),
+            authority: rating.authority,
+            country: rating.country.clone(),
+            languages,
+        })
+    }
+}
+
+/// Use the ratings in `config` from now on
+///
+/// # Panics
+/// If a file can't be read or isn't in its format
+pub fn configure(config: &SourceConfig) {
+    *REGISTRY.write().unwrap() = Some(Arc::new(SourceRegistry::from_config(config)));
+}
+
+/// Use the `SourceConfig` in a JSON file, for the commands
+///
+/// # Panics
+/// If the file or a rating file it lists can't be read or is invalid
+pub fn configure_from_file(path: &str) {
+    let file = read_to_string(path).expect("Could not read source configuration");
+    configure(&serde_json::from_str(file.as_str()).expect("Invalid source configuration"));
+}
+
+/// The registry in use, the default one until `configure` is called
+pub fn registry() -> Arc<SourceRegistry> {
+    if let Some(registry) = REGISTRY.read().unwrap().as_ref() {
+        return registry.clone();
+    }
+    let mut registry = REGISTRY.write().unwrap();
+    registry
+        .get_or_insert_with(|| Arc::new(SourceRegistry::from_config(&SourceConfig::default())))
+        .clone()
+}
+
+/// Entry point for `tgnews source`, prints what `site`(a host or a URL) resolves to
+pub fn entry(site: &str) {
+    let site = if site.contains("://") {
+        host(site).unwrap_or_else(|| panic!("Could not get a host out of {:?}", site))
+    } else {
+        site.trim_start_matches("www.").to_lowercase()
+    };
+    match registry().resolve(site.as_str()) {
+        Some(resolved) => println!("{}", serde_json::to_string_pretty(&resolved).unwrap()),
+        None => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({"host": site, "authority": 0.0})).unwrap()
+        ),
+    }
+}
@@ -12,7 +12,10 @@ use crate::categories::enums::Categories::{
     Economy, Entertainment, Other, Science, Society, Sports, Technology,
 };
 use crate::categories::{classify_en_with_accuracy, classify_ru_with_accuracy};
+use crate::duplicates::{simhash, DuplicateIndex};
+use crate::embeddings::{embed, fallbacks, representation};
 use crate::news::{is_news, is_news_ru};
+use crate::ranking::{collapse_threads, rank_threads, sort_threads, SortBy};
 use crate::slink::{slink, Articles};
 use crate::threads::annotator::Annotator;
 use crate::threads::annotator::FileAnnotator;
@@ -89,10 +92,11 @@ fn classify(paths: &[String], en_clone: Arc<Mutex<Annotator>>, ru_clone: Arc<Mut
                     .timestamp();
                     let language = lang.lang();
                     let cleaned = clean(body.clone(), false);
+                    let simhash = simhash(cleaned.as_str());
 
                     let (text, accuracy) = {
                         match language {
-                            Lang::Eng => classify_en_with_accuracy(url.clone(), cleaned),
+                            Lang::Eng => classify_en_with_accuracy(url.clone(), cleaned.clone()),
                             Lang::Rus => {
                                 classify_ru_with_accuracy(title.clone(), url.clone(), body)
                             }
@@ -110,7 +114,8 @@ fn classify(paths: &[String], en_clone: Arc<Mutex<Annotator>>, ru_clone: Arc<Mut
                             url,
                             text,
                             published_time,
-                            EN_VECTORS.get_sentence_vector(clean(title, true).as_str()),
+                            embed(&EN_VECTORS, clean(title, true).as_str(), cleaned.as_str()).0,
+                            simhash,
                         ),
                         Lang::Rus => ru_clone.lock().expect("Could not acquire lock\n").push(
                             title.clone(),
@@ -119,7 +124,8 @@ fn classify(paths: &[String], en_clone: Arc<Mutex<Annotator>>, ru_clone: Arc<Mut
                             url,
                             text,
                             published_time,
-                            RU_VECTORS.get_sentence_vector(title.as_str()),
+                            embed(&RU_VECTORS, title.as_str(), cleaned.as_str()).0,
+                            simhash,
                         ),
                         _ => (),
                     }
@@ -128,7 +134,10 @@ fn classify(paths: &[String], en_clone: Arc<Mutex<Annotator>>, ru_clone: Arc<Mut
         }
     }
 }
-pub fn entry(dir: &str, threads: usize) {
+/// Cluster the files in `dir` and print the threads
+///
+/// Threads are sorted if `sort` is given, and keep one article per host if `collapse` is set
+pub fn entry(dir: &str, threads: usize, sort: Option<SortBy>, collapse: bool) {
     assert!(Path::new(dir).exists(), "Paths {:?} doesn't exist", dir);
     let small_paths = split_files_for_threads(dir.to_string(), threads);
     let en = Arc::new(Mutex::new(Annotator::new()));
@@ -141,8 +150,14 @@ pub fn entry(dir: &str, threads: usize) {
         }
     })
     .expect("Could not spawn threads");
-    let u = &*en.lock().unwrap();
-    let v = &*ru.lock().unwrap();
+    let mut u = en.lock().unwrap();
+    let mut v = ru.lock().unwrap();
+    // articles are scored against the newest one, like the threads below
+    let newest = u.newest().max(v.newest());
+    u.score(newest);
+    v.score(newest);
+    let u = &*u;
+    let v = &*v;
     let f = Arc::new(Mutex::new(Vec::with_capacity(10000)));
     crossbeam_utils::thread::scope(|s| {
         let g = f.clone();
@@ -151,11 +166,43 @@ pub fn entry(dir: &str, threads: usize) {
         s.spawn(move |_| cluster_files(v, h));
     })
     .expect("Could not spawn clustering threads");
+    let mut output = f.lock().unwrap();
+    mark_duplicates(&mut *output, u, v);
+    let now = output
+        .iter()
+        .flat_map(|a| a.article_times.iter())
+        .max()
+        .copied()
+        .unwrap_or(0);
+    match sort {
+        Some(sort) => sort_threads(&mut *output, sort, now),
+        None => rank_threads(&mut *output, now),
+    }
+    if collapse {
+        collapse_threads(&mut *output);
+    }
     // finally print output
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&*f.lock().unwrap()).unwrap()
-    );
+    let output = json!({
+        "representation": representation().name(),
+        "title_fallbacks": fallbacks(),
+        "threads": &*output,
+    });
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+}
+/// Mark the articles of each thread that are near duplicates of another article
+fn mark_duplicates(output: &mut [Articles], en: &Annotator, ru: &Annotator) {
+    let mut index = DuplicateIndex::new();
+    for file in en.get_categories().iter().chain(ru.get_categories().iter()) {
+        index.insert(file.file.as_str(), file.simhash, file.time.max(0) as u64);
+    }
+    let originals = index.originals();
+    for thread in output.iter_mut() {
+        for article in thread.articles.iter() {
+            if let Some(original) = originals.get(article) {
+                thread.duplicate_of.insert(article.clone(), original.clone());
+            }
+        }
+    }
 }
 /// CLuster files
 fn cluster_files(files: &Annotator, f: Arc<Mutex<Vec<Articles>>>) {
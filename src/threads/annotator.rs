@@ -1,10 +1,8 @@
-use crate::alexa::ALEXA_RATINGS;
 use crate::categories::enums::Categories;
+use crate::ranking::article_importance;
+use crate::utils::host;
 use serde::export::Formatter;
 use std::fmt::Debug;
-use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
-use url::Url;
 
 #[derive(Default, Clone)]
 pub struct FileAnnotator {
@@ -15,6 +13,11 @@ pub struct FileAnnotator {
     pub time: i64,
     pub importance: i32,
     pub vectors: Vec<f32>,
+    pub category: Categories,
+    /// Host of `url`, empty if it has none
+    pub host: String,
+    /// SimHash of the body, see `duplicates.rs`
+    pub simhash: u64,
 }
 impl PartialEq for FileAnnotator {
     fn eq(&self, other: &Self) -> bool {
@@ -33,6 +36,7 @@ impl Debug for FileAnnotator {
     }
 }
 impl FileAnnotator {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         title: String,
         accuracy: f32,
@@ -40,39 +44,33 @@ impl FileAnnotator {
         time: i64,
         url: String,
         vectors: Vec<f32>,
+        category: Categories,
+        simhash: u64,
     ) -> FileAnnotator {
-        let mut a = FileAnnotator {
+        FileAnnotator {
             title,
             accuracy,
             time,
+            host: host(url.as_str()).unwrap_or_default(),
             url,
             file,
+            // scored once we know every article, see `Annotator::score`
             importance: 0,
             vectors,
-        };
-        a.calc_importance();
-        a
+            category,
+            simhash,
+        }
     }
-    pub fn calc_importance(&mut self) {
-        let accuracy = (self.accuracy * 10.0).round() as i32;
-        let time_diff = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            - self.time;
-        let rating = {
-            let host = Url::from_str(self.url.as_str())
-                .unwrap()
-                .host_str()
-                .unwrap()
-                .replace("www.", "");
-            if let Some(ratings) = ALEXA_RATINGS.get(&host) {
-                ratings.get_rating().round() as i32
-            } else {
-                1
-            }
-        };
-        self.importance = accuracy * rating / ((time_diff as f32).log(10.0).round()) as i32;
+    /// Score the article with the shared ranking model, see `ranking::article_importance`
+    pub fn calc_importance(&mut self, now: u64) {
+        let importance = article_importance(
+            self.accuracy,
+            self.host.as_str(),
+            self.time.max(0) as u64,
+            now,
+        );
+        // two decimals are plenty to tell articles apart
+        self.importance = (importance * 100.0).round() as i32;
     }
 }
 #[derive(Debug, Default, Clone)]
@@ -99,6 +97,7 @@ impl Annotator {
             all: Vec::with_capacity(10000),
         }
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn push(
         &mut self,
         title: String,
@@ -108,6 +107,7 @@ impl Annotator {
         category: Categories,
         time: i64,
         values: Vec<f32>,
+        simhash: u64,
     ) {
         self.all.push(FileAnnotator::new(
             title.clone(),
@@ -116,29 +116,31 @@ impl Annotator {
             time,
             url.clone(),
             values.clone(),
+            category,
+            simhash,
         ));
         match category {
             Categories::Society => self
                 .society
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Sports => self
                 .sports
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Technology => self
                 .technology
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Entertainment => self
                 .entertainment
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Other => self
                 .other
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Science => self
                 .science
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Economy => self
                 .economy
-                .push(FileAnnotator::new(title, accuracy, file, time, url, values)),
+                .push(FileAnnotator::new(title, accuracy, file, time, url, values, category, simhash)),
             Categories::Unknown => (),
         }
     }
@@ -154,6 +156,28 @@ impl Annotator {
             Categories::Unknown => unreachable!(),
         }
     }
+    /// Publish time of the newest article, 0 if there are none
+    #[allow(clippy::cast_sign_loss)]
+    pub fn newest(&self) -> u64 {
+        self.all.iter().map(|file| file.time.max(0) as u64).max().unwrap_or(0)
+    }
+    /// Score every article against `now`, see `FileAnnotator::calc_importance`
+    pub fn score(&mut self, now: u64) {
+        for files in [
+            &mut self.society,
+            &mut self.economy,
+            &mut self.technology,
+            &mut self.entertainment,
+            &mut self.sports,
+            &mut self.science,
+            &mut self.other,
+            &mut self.all,
+        ]
+        .iter_mut()
+        {
+            files.iter_mut().for_each(|file| file.calc_importance(now));
+        }
+    }
     pub fn get_categories(&self) -> Vec<FileAnnotator> {
         self.all.clone()
     }
@@ -1,6 +1,7 @@
-use crate::logger::tgnews_debug;
 use regex::Regex;
 use std::collections::HashSet;
+use std::str::FromStr;
+use url::Url;
 use walkdir::WalkDir;
 
 lazy_static! {
@@ -44,6 +45,33 @@ pub fn clean(doc: String, stem: bool) -> String {
         .to_string()
 }
 
+/// The host of a URL without a leading `www.`, as sources are keyed in the source registry
+///
+/// Returns `None` if the URL can't be parsed or has no host
+pub fn host(url: &str) -> Option<String> {
+    let url = Url::from_str(url).ok()?;
+    Some(url.host_str()?.trim_start_matches("www.").to_string())
+}
+
+/// 64 bit FNV-1a
+///
+/// We don't use `DefaultHasher` because its output is allowed to change between rust releases
+/// and these hashes are stored in the database
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Hash a document's content using `fnv1a`
+///
+/// Returns the hash as a 16 character lowercase hex string
+pub fn content_hash(doc: &str) -> String {
+    format!("{:016x}", fnv1a(doc.as_bytes()))
+}
+
 /// Iterate over all entries in a folder and extract all files
 ///
 /// Split those files into groups for each worker thread
@@ -57,7 +85,7 @@ pub fn split_files_for_threads(root: String, worker_threads: usize) -> Vec<Vec<S
             paths.push(String::from(entry.path().to_str().unwrap()));
         }
     }
-    tgnews_debug(format!("Read {} files", paths.len()));
+    debug!(files = paths.len(); "Read files");
     // Divide paths into segments.
     let size = paths.len() / worker_threads;
     let mut start: usize = 0;